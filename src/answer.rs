@@ -0,0 +1,188 @@
+use crate::json::Json;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// The answer to one part of a puzzle.
+///
+/// Answers compare by value, so `Unsigned(5)`, `Signed(5)` and `Big(5)` are all equal, whichever
+/// type the day happened to compute with. Text answers only equal other text.
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    Big(i128),
+    Text(String),
+}
+
+/// The text a day's `part1` or `part2` returns before it has been solved.
+pub const UNSOLVED: &str = "not solved yet";
+
+impl Answer {
+    /// The answer for a part that has not been solved yet. It is never cached or submitted.
+    pub fn unsolved() -> Answer {
+        Answer::Text(UNSOLVED.to_string())
+    }
+
+    pub fn is_unsolved(&self) -> bool {
+        matches!(self, Answer::Text(text) if text == UNSOLVED)
+    }
+
+    /// Reads an answer as written by `Display`: an integer if it looks like one, text otherwise.
+    pub fn parse(text: &str) -> Answer {
+        match text.parse::<i128>() {
+            Ok(n) => Answer::Big(n).normalized(),
+            Err(_) => Answer::Text(text.to_string()),
+        }
+    }
+
+    /// The answer's value if it is a number.
+    pub fn as_integer(&self) -> Option<i128> {
+        match *self {
+            Answer::Signed(n) => Some(n.into()),
+            Answer::Unsigned(n) => Some(n.into()),
+            Answer::Big(n) => Some(n),
+            Answer::Text(_) => None,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        match self {
+            Answer::Text(text) => Json::from(text.as_str()),
+            number => Json::Number(number.to_string()),
+        }
+    }
+
+    // Picks the narrowest variant that holds a big integer.
+    fn normalized(self) -> Answer {
+        match self {
+            Answer::Big(n) if n >= 0 && n <= u64::MAX.into() => Answer::Unsigned(n as u64),
+            Answer::Big(n) if n >= i64::MIN.into() && n < 0 => Answer::Signed(n as i64),
+            other => other,
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Answer {}
+
+/// Numbers order by value and come before all text.
+impl Ord for Answer {
+    fn cmp(&self, other: &Answer) -> Ordering {
+        match (self, other) {
+            (Answer::Text(a), Answer::Text(b)) => a.cmp(b),
+            (Answer::Text(_), _) => Ordering::Greater,
+            (_, Answer::Text(_)) => Ordering::Less,
+            (a, b) => a.as_integer().cmp(&b.as_integer()),
+        }
+    }
+}
+
+impl PartialOrd for Answer {
+    fn partial_cmp(&self, other: &Answer) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Answer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Answer::Text(text) => text.hash(state),
+            number => number.as_integer().hash(state),
+        }
+    }
+}
+
+/// Compares against an answer written out, as in an answers file or a test.
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        *self == Answer::parse(other)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Big(n) => write!(f, "{}", n),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Answer {
+        Answer::Unsigned(n as u64)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Answer {
+        Answer::Unsigned(n)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(n: isize) -> Answer {
+        Answer::Signed(n as i64)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Answer {
+        Answer::Signed(n)
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(n: i128) -> Answer {
+        Answer::Big(n)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compare_across_types() {
+        assert_eq!(Answer::from(5usize), Answer::from(5isize));
+        assert_eq!(Answer::from(5i128), Answer::from(5u64));
+        assert_ne!(Answer::from(-5isize), Answer::from(5usize));
+        assert_ne!(Answer::from(5usize), Answer::Text("5".into()));
+        assert!(Answer::from(-1isize) < Answer::from(0usize));
+        assert!(Answer::from(u64::MAX) < Answer::from(i128::from(u64::MAX) + 1));
+    }
+
+    #[test]
+    fn parse_and_print() {
+        for text in &["0", "-42", "18446744073709551616", "abc", "4x"] {
+            assert_eq!(Answer::parse(text).to_string(), *text);
+        }
+        assert!(matches!(Answer::parse("12"), Answer::Unsigned(12)));
+        assert!(matches!(Answer::parse("-12"), Answer::Signed(-12)));
+        assert!(matches!(Answer::parse("ab"), Answer::Text(_)));
+        assert_eq!(Answer::from(241861950usize), "241861950");
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(Answer::from(-3isize).to_json().to_string(), "-3");
+        assert_eq!(
+            Answer::Text("cbgefad".into()).to_json().to_string(),
+            r#""cbgefad""#
+        );
+    }
+}
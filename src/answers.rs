@@ -0,0 +1,204 @@
+use crate::answer::Answer;
+use crate::error::{parse_number, LineError, ParseError};
+use crate::runner::Report;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The checked-in file of known answers.
+pub const DEFAULT_PATH: &str = "answers.txt";
+
+/// Expected answers, keyed by day and part.
+///
+/// The file format has one answer per line, as `day part answer`. Blank lines and lines starting
+/// with `#` are ignored.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers(BTreeMap<(usize, usize), Answer>);
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, ParseError> {
+        let mut answers = Answers::default();
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let (day, part, answer) = parse_line(line).map_err(|e| e.at(i + 1, line))?;
+            answers.set(day, part, answer);
+        }
+        Ok(answers)
+    }
+
+    /// Loads the answers at `path`, treating a missing file as having no answers.
+    pub fn load(path: &str) -> crate::error::Result<Answers> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Answers::parse(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.0.get(&(day, part))
+    }
+
+    pub fn set(&mut self, day: usize, part: usize, answer: Answer) {
+        self.0.insert((day, part), answer);
+    }
+
+    /// The days with at least one known answer, in order.
+    pub fn days(&self) -> Vec<usize> {
+        let mut days: Vec<usize> = self.0.keys().map(|&(day, _)| day).collect();
+        days.dedup();
+        days
+    }
+
+    /// Compares the answers in `report` against the expected ones.
+    ///
+    /// Parts without an expected answer are not checked.
+    pub fn check(&self, report: &Report) -> Vec<Mismatch> {
+        let actual = [&report.part1, &report.part2];
+        (1..=2)
+            .filter_map(|part| {
+                let expected = self.get(report.day, part)?;
+                let actual = actual[part - 1].as_ref();
+                if actual == Some(expected) {
+                    None
+                } else {
+                    Some(Mismatch {
+                        day: report.day,
+                        part,
+                        expected: expected.clone(),
+                        actual: actual.cloned(),
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Records the answers from `report`, leaving parts that failed untouched.
+    pub fn record(&mut self, report: &Report) {
+        if let Some(answer) = &report.part1 {
+            self.set(report.day, 1, answer.clone());
+        }
+        if let Some(answer) = &report.part2 {
+            self.set(report.day, 2, answer.clone());
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<(usize, usize, Answer), LineError> {
+    let mut fields = line.split_whitespace();
+    match (fields.next(), fields.next(), fields.next(), fields.next()) {
+        (Some(day), Some(part_text), Some(answer), None) => {
+            let day = parse_number(line, day)?;
+            let part = parse_number(line, part_text)?;
+            if part != 1 && part != 2 {
+                return Err(LineError::within(line, part_text, "part must be 1 or 2"));
+            }
+            Ok((day, part, Answer::parse(answer)))
+        }
+        _ => Err(LineError::whole(line, "expected `day part answer`")),
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# day part answer")?;
+        for ((day, part), answer) in &self.0 {
+            writeln!(f, "{} {} {}", day, part, answer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A part whose answer did not match the expected one.
+#[derive(Debug, PartialEq)]
+pub struct Mismatch {
+    pub day: usize,
+    pub part: usize,
+    pub expected: Answer,
+    /// `None` if the part did not produce an answer.
+    pub actual: Option<Answer>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "day{} part {}", self.day, self.part)?;
+        writeln!(f, "- {}", self.expected)?;
+        match &self.actual {
+            Some(actual) => writeln!(f, "+ {}", actual),
+            None => writeln!(f, "+ (no answer)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "# day part answer
+1 1 514579
+1 2 241861950
+
+3 1 7
+";
+
+    fn report(day: usize, part1: Option<&str>, part2: Option<&str>) -> Report {
+        let mut report = Report::new(day);
+        report.part1 = part1.map(Answer::parse);
+        report.part2 = part2.map(Answer::parse);
+        report
+    }
+
+    #[test]
+    fn parse_and_print() {
+        let answers = Answers::parse(EXAMPLE).unwrap();
+        assert_eq!(answers.get(1, 2), Some(&Answer::from(241861950usize)));
+        assert_eq!(answers.get(3, 2), None);
+        assert_eq!(answers.days(), vec![1, 3]);
+        assert_eq!(answers.to_string(), EXAMPLE.replace("\n\n", "\n"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Answers::parse("1 1 5\n1 3 6\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        let error = Answers::parse("1 1\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+    }
+
+    #[test]
+    fn check_report() {
+        let answers = Answers::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            answers.check(&report(1, Some("514579"), Some("241861950"))),
+            vec![]
+        );
+        assert_eq!(
+            answers.check(&report(1, Some("514579"), None)),
+            vec![Mismatch {
+                day: 1,
+                part: 2,
+                expected: Answer::from(241861950usize),
+                actual: None,
+            }]
+        );
+        // Only part 1 of day 3 is known, so part 2 is not checked.
+        assert_eq!(
+            answers.check(&report(3, Some("7"), Some("anything"))),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn record_report() {
+        let mut answers = Answers::parse(EXAMPLE).unwrap();
+        answers.record(&report(3, Some("8"), None));
+        assert_eq!(answers.get(3, 1), Some(&Answer::from(8usize)));
+        assert_eq!(answers.get(3, 2), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! `batch`: runs one day over every input in a directory, to check a solver against inputs other
+//! than our own.
+
+use crate::budget::Budget;
+use crate::input::Source;
+use crate::runner::{self, Failure, Report};
+use crate::solver::Day;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The outcome for one input file.
+pub struct Row {
+    pub path: PathBuf,
+    pub report: Report,
+}
+
+impl Row {
+    // The file's name, which is all that tells rows from the same directory apart.
+    fn name(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |name| name.to_string_lossy().into(),
+        )
+    }
+}
+
+/// The files in `dir`, by name. Subdirectories and hidden files are skipped.
+pub fn inputs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Runs `day` over each file in `dir` in turn, with `budget` for each phase of each run.
+pub fn run(day: &Day, dir: &Path, budget: Budget) -> io::Result<Vec<Row>> {
+    Ok(inputs(dir)?
+        .into_iter()
+        .map(|path| {
+            let report = match Source::File(path.clone()).open() {
+                Ok(mut input) => runner::run_day_within(day, &mut input, budget),
+                Err(e) => Report::failed(day.number, Failure::Input, e.to_string()),
+            };
+            Row { path, report }
+        })
+        .collect())
+}
+
+/// Lays out `rows` as a table, one row per file.
+pub fn table(rows: &[Row]) -> String {
+    let rows: Vec<[String; 5]> = rows
+        .iter()
+        .map(|row| {
+            let report = &row.report;
+            let times = report.times;
+            [
+                row.name(),
+                report
+                    .part1
+                    .as_ref()
+                    .map_or("-".to_string(), ToString::to_string),
+                report
+                    .part2
+                    .as_ref()
+                    .map_or("-".to_string(), ToString::to_string),
+                format!("{:.2?}", times.parse + times.part1 + times.part2),
+                match &report.error {
+                    None => "ok".into(),
+                    // Panic messages can span several lines; keep each file on one row.
+                    Some(error) => format!(
+                        "FAILED: {}",
+                        error.split_whitespace().collect::<Vec<_>>().join(" ")
+                    ),
+                },
+            ]
+        })
+        .collect();
+
+    let header = ["File", "Part 1", "Part 2", "Time", "Status"].map(String::from);
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            format!(
+                "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}  {}\n",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3]
+            )
+        })
+        .collect()
+}
+
+/// Writes `rows` as CSV with a header line. Times are in nanoseconds, as in the JSON reports, and
+/// a part without an answer is left empty.
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut csv = String::from("file,part1,part2,parse_ns,part1_ns,part2_ns,error\n");
+    for row in rows {
+        let report = &row.report;
+        let fields = [
+            row.name(),
+            report
+                .part1
+                .as_ref()
+                .map_or(String::new(), ToString::to_string),
+            report
+                .part2
+                .as_ref()
+                .map_or(String::new(), ToString::to_string),
+            report.times.parse.as_nanos().to_string(),
+            report.times.part1.as_nanos().to_string(),
+            report.times.part2.as_nanos().to_string(),
+            report.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+// Quotes a field if it holds anything CSV treats specially.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+    use crate::testing::Scratch;
+
+    // A scratch directory holding `files` and an empty subdirectory.
+    fn scratch_inputs(name: &str, files: &[(&str, &str)]) -> Scratch {
+        let scratch = Scratch::new(&format!("batch-{}", name));
+        fs::create_dir(scratch.join("subdir")).unwrap();
+        for (name, contents) in files {
+            fs::write(scratch.join(name), contents).unwrap();
+        }
+        scratch
+    }
+
+    #[test]
+    fn runs_every_file() {
+        let scratch = scratch_inputs(
+            "day1",
+            &[
+                ("bob.txt", "1721\n979\n366\n299\n675\n1456\n"),
+                ("alice.txt", "1721\nabc\n"),
+                (".hidden", "not an input"),
+            ],
+        );
+        let rows = run(find_day("day1").unwrap(), scratch.path(), Budget::default()).unwrap();
+
+        let names: Vec<String> = rows.iter().map(Row::name).collect();
+        assert_eq!(names, ["alice.txt", "bob.txt"]);
+        assert_eq!(rows[0].report.failure, Some(Failure::Input));
+        assert_eq!(rows[1].report.part2, Some(241861950u64.into()));
+
+        let table = table(&rows);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(
+            lines[0].starts_with("File       Part 1     Part 2"),
+            "{}",
+            table
+        );
+        assert!(
+            lines[1].starts_with("alice.txt       -          -"),
+            "{}",
+            table
+        );
+        assert!(lines[1].ends_with(
+            "FAILED: parse failed: day1 line 2, column 1: expected a number, found `abc`"
+        ));
+        assert!(
+            lines[2].starts_with("bob.txt    514579  241861950"),
+            "{}",
+            table
+        );
+        assert!(lines[2].ends_with("  ok"));
+
+        let csv = to_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "file,part1,part2,parse_ns,part1_ns,part2_ns,error"
+        );
+        assert!(lines[1].starts_with("alice.txt,,,"), "{}", csv);
+        assert!(lines[2].starts_with("bob.txt,514579,241861950,"), "{}", csv);
+        assert!(lines[2].ends_with(','));
+    }
+
+    #[test]
+    fn missing_directory() {
+        let scratch = Scratch::new("batch-missing");
+        let dir = scratch.join("missing");
+        assert!(run(find_day("day1").unwrap(), &dir, Budget::default()).is_err());
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\"\nthere"), "\"say \"\"hi\"\"\nthere\"");
+    }
+}
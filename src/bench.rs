@@ -0,0 +1,84 @@
+use crate::runner::{run_day, Times};
+use crate::solver::Day;
+use std::time::Duration;
+
+/// The spread of a set of timing samples.
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarizes `samples`, which must not be empty.
+    pub fn of(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Timings for each phase of a day over several iterations.
+pub struct Benchmark {
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+/// Runs `day` over `input` `iterations` times.
+///
+/// Each iteration parses the input afresh, so the parts never see state left behind by an earlier
+/// run. Returns the error from the first failed iteration, if any.
+pub fn bench_day(day: &Day, input: &[u8], iterations: usize) -> Result<Benchmark, String> {
+    let mut samples: Vec<Times> = Vec::new();
+    for _ in 0..iterations.max(1) {
+        let report = run_day(day, &mut &input[..]);
+        if let Some(error) = report.error {
+            return Err(error);
+        }
+        samples.push(report.times);
+    }
+
+    Ok(Benchmark {
+        parse: Stats::of(samples.iter().map(|t| t.parse).collect()),
+        part1: Stats::of(samples.iter().map(|t| t.part1).collect()),
+        part2: Stats::of(samples.iter().map(|t| t.part2).collect()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+
+    #[test]
+    fn stats() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            Stats::of(vec![ms(5), ms(1), ms(3), ms(9), ms(4)]),
+            Stats {
+                min: ms(1),
+                median: ms(4),
+                max: ms(9)
+            }
+        );
+    }
+
+    #[test]
+    fn bench_example() {
+        let day = find_day("day1").unwrap();
+        let input = b"1721\n979\n366\n299\n675\n1456\n";
+        let benchmark = bench_day(day, input, 3).unwrap();
+        assert!(benchmark.parse.min <= benchmark.parse.max);
+    }
+
+    #[test]
+    fn bench_failure() {
+        let day = find_day("day1").unwrap();
+        assert!(bench_day(day, b"x\n", 3).is_err());
+    }
+}
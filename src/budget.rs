@@ -0,0 +1,172 @@
+//! Time and step limits for solvers, checked cooperatively.
+//!
+//! Solvers call `spend` once per round of any loop or recursion that a bad input could keep going
+//! forever. Nothing is limited unless a budget is installed on the current thread with `limit`.
+//! Once it runs out, `spend` unwinds out of the solver with an `Exceeded` describing how far it
+//! got, and `limit` returns that as an error.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+/// How long a solver may run and how many steps it may take. `None` is no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Budget {
+    pub time: Option<Duration>,
+    pub steps: Option<u64>,
+}
+
+/// How far a solver got before its budget ran out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exceeded {
+    pub steps: u64,
+    pub elapsed: Duration,
+    /// What the solver was doing, in its own words.
+    pub progress: String,
+}
+
+impl fmt::Display for Exceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "budget exceeded after {} steps in {:.1?}: {}",
+            self.steps, self.elapsed, self.progress
+        )
+    }
+}
+
+struct State {
+    budget: Budget,
+    started: Instant,
+    steps: u64,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Takes one step, unwinding out of the solver if that exhausts the budget.
+///
+/// `progress` describes the step, and is only called if the budget has run out.
+pub fn spend<P: FnOnce() -> String>(progress: P) {
+    let exceeded = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state.as_mut()?;
+        state.steps += 1;
+        let elapsed = state.started.elapsed();
+        let out_of_steps = state.budget.steps.is_some_and(|steps| state.steps > steps);
+        let out_of_time = state.budget.time.is_some_and(|time| elapsed > time);
+        if out_of_steps || out_of_time {
+            Some((state.steps, elapsed))
+        } else {
+            None
+        }
+    });
+    if let Some((steps, elapsed)) = exceeded {
+        panic::panic_any(Exceeded {
+            steps,
+            elapsed,
+            progress: progress(),
+        });
+    }
+}
+
+/// Runs `f` within `budget`, starting from no steps taken.
+///
+/// Other panics pass through untouched. The previous budget, if any, is put back afterwards.
+pub fn limit<T, F: FnOnce() -> T>(budget: Budget, f: F) -> Result<T, Exceeded> {
+    struct Restore(Option<State>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            STATE.with(|state| *state.borrow_mut() = previous);
+        }
+    }
+
+    let state = State {
+        budget,
+        started: Instant::now(),
+        steps: 0,
+    };
+    let restore = Restore(STATE.with(|current| current.borrow_mut().replace(state)));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    drop(restore);
+    result.map_err(|payload| match payload.downcast::<Exceeded>() {
+        Ok(exceeded) => *exceeded,
+        Err(payload) => panic::resume_unwind(payload),
+    })
+}
+
+/// Keeps the panic hook from printing anything, for commands that report every failure
+/// themselves.
+pub fn silent_panic_hook() {
+    panic::set_hook(Box::new(|_| {}));
+}
+
+/// Keeps the panic hook from printing anything when a budget runs out, since `limit` reports that
+/// itself. Other panics are printed as before.
+pub fn quiet_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !info.payload().is::<Exceeded>() {
+            hook(info);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn count_forever() -> u64 {
+        let mut n = 0u64;
+        loop {
+            spend(|| format!("counted to {}", n));
+            n += 1;
+        }
+    }
+
+    #[test]
+    fn unlimited_without_budget() {
+        for _ in 0..1000 {
+            spend(|| unreachable!());
+        }
+        assert_eq!(limit(Budget::default(), || 5), Ok(5));
+    }
+
+    #[test]
+    fn step_limit() {
+        let budget = Budget {
+            steps: Some(10),
+            ..Budget::default()
+        };
+        let exceeded = limit(budget, count_forever).unwrap_err();
+        assert_eq!(exceeded.steps, 11);
+        assert_eq!(exceeded.progress, "counted to 10");
+        assert!(exceeded
+            .to_string()
+            .starts_with("budget exceeded after 11 steps"));
+
+        // Each run starts afresh, and nothing is left installed afterwards.
+        assert!(limit(budget, count_forever).is_err());
+        spend(|| unreachable!());
+    }
+
+    #[test]
+    fn time_limit() {
+        let budget = Budget {
+            time: Some(Duration::from_millis(20)),
+            ..Budget::default()
+        };
+        let exceeded = limit(budget, count_forever).unwrap_err();
+        assert!(exceeded.elapsed >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn other_panics_pass_through() {
+        let result = panic::catch_unwind(|| limit(Budget::default(), || panic!("boom")));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,358 @@
+//! An on-disk cache of answers, so a slow day is not solved again for an input it has already
+//! seen.
+//!
+//! Answers are keyed by day, part, the day's solver version and a hash of the input bytes. Bumping
+//! a day's version in `DAYS` leaves its old entries unreachable, and they are dropped the next
+//! time the cache is loaded.
+
+use crate::answer::Answer;
+use crate::budget::Budget;
+use crate::error::{parse_number, LineError, ParseError};
+use crate::hash::fnv1a;
+use crate::input::Source;
+use crate::runner::{self, Report};
+use crate::solver::{Day, DAYS};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Where the cache is kept by default.
+pub const DEFAULT_PATH: &str = ".cache/answers.txt";
+
+/// What an answer was computed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Key {
+    pub day: usize,
+    pub part: usize,
+    pub version: u32,
+    /// A hash of the whole input.
+    pub input: u64,
+}
+
+impl Key {
+    pub fn new(day: &Day, part: usize, input: &[u8]) -> Key {
+        Key {
+            day: day.number,
+            part,
+            version: day.version,
+            input: fnv1a(input),
+        }
+    }
+}
+
+/// Cached answers.
+///
+/// The file format has one answer per line, as `day part version input answer`, where `input` is
+/// the hash of the input in hex. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Cache {
+    answers: BTreeMap<Key, Answer>,
+    changed: bool,
+}
+
+impl Cache {
+    pub fn parse(text: &str) -> Result<Cache, ParseError> {
+        let mut cache = Cache::default();
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let (key, answer) = parse_line(line).map_err(|e| e.at(i + 1, line))?;
+            cache.answers.insert(key, answer);
+        }
+        Ok(cache)
+    }
+
+    /// Loads the cache at `path`, treating a missing file as an empty cache. Entries from older
+    /// versions of a solver, or for days that no longer exist, are dropped.
+    pub fn load(path: &Path) -> crate::error::Result<Cache> {
+        let mut cache = match fs::read_to_string(path) {
+            Ok(text) => Cache::parse(&text)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Cache::default(),
+            Err(e) => return Err(e.into()),
+        };
+        cache.retain_current(|number| {
+            DAYS.iter()
+                .find(|day| day.number == number)
+                .map(|day| day.version)
+        });
+        Ok(cache)
+    }
+
+    /// Writes the cache to `path`, creating its directory if need be.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Another run reading the cache should never see half of it.
+        let partial = path.with_extension("partial");
+        fs::write(&partial, self.to_string())?;
+        fs::rename(&partial, path)
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&Answer> {
+        self.answers.get(key)
+    }
+
+    pub fn insert(&mut self, key: Key, answer: Answer) {
+        // Solving the part changes the answer without changing the input or, usually, the version.
+        if answer.is_unsolved() {
+            return;
+        }
+        if self.answers.get(&key) != Some(&answer) {
+            self.answers.insert(key, answer);
+            self.changed = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// Whether anything has been dropped or added since the cache was loaded, so that it needs
+    /// saving.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// A report of both answers for `input`, if both are cached.
+    pub fn lookup(&self, day: &Day, input: &[u8]) -> Option<Report> {
+        let mut report = Report::new(day.number);
+        report.part1 = Some(self.get(&Key::new(day, 1, input))?.clone());
+        report.part2 = Some(self.get(&Key::new(day, 2, input))?.clone());
+        Some(report)
+    }
+
+    /// Caches the answers in `report`, which came from `input`.
+    pub fn record(&mut self, day: &Day, input: &[u8], report: &Report) {
+        for (part, answer) in [(1, &report.part1), (2, &report.part2)] {
+            if let Some(answer) = answer {
+                self.insert(Key::new(day, part, input), answer.clone());
+            }
+        }
+    }
+
+    // Keeps only the entries whose version is the current one for their day.
+    fn retain_current<V: Fn(usize) -> Option<u32>>(&mut self, current_version: V) {
+        let before = self.answers.len();
+        self.answers
+            .retain(|key, _| current_version(key.day) == Some(key.version));
+        self.changed |= self.answers.len() != before;
+    }
+}
+
+impl fmt::Display for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# day part version input answer")?;
+        for (key, answer) in &self.answers {
+            writeln!(
+                f,
+                "{} {} {} {:016x} {}",
+                key.day, key.part, key.version, key.input, answer
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_line(line: &str) -> Result<(Key, Answer), LineError> {
+    // The answer comes last and takes the rest of the line.
+    let mut fields = line.trim().splitn(5, ' ');
+    match (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    ) {
+        (Some(day), Some(part_text), Some(version), Some(input), Some(answer)) => {
+            let day = parse_number(line, day)?;
+            let part = parse_number(line, part_text)?;
+            if part != 1 && part != 2 {
+                return Err(LineError::within(line, part_text, "part must be 1 or 2"));
+            }
+            let version = parse_number(line, version)?;
+            let input = u64::from_str_radix(input, 16)
+                .map_err(|_| LineError::within(line, input, "expected a hash in hex"))?;
+            let key = Key {
+                day,
+                part,
+                version,
+                input,
+            };
+            Ok((key, Answer::parse(answer)))
+        }
+        _ => Err(LineError::whole(
+            line,
+            "expected `day part version input answer`",
+        )),
+    }
+}
+
+/// Deletes the cache at `path`, returning how many answers it held.
+pub fn clear(path: &Path) -> io::Result<usize> {
+    // A cache that cannot be parsed is cleared all the same.
+    let count = match fs::read_to_string(path) {
+        Ok(text) => Cache::parse(&text).map_or(0, |cache| cache.len()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    fs::remove_file(path)?;
+    Ok(count)
+}
+
+/// Like `runner::run_day_within`, but answered from `cache` if both answers for `input` are in
+/// it. Otherwise the day is run and the answers it comes up with are cached.
+pub fn run_day(cache: &mut Cache, day: &Day, input: &[u8], budget: Budget) -> Report {
+    if let Some(report) = cache.lookup(day, input) {
+        return report;
+    }
+    let report = runner::run_day_within(day, &mut &input[..], budget);
+    cache.record(day, input, &report);
+    report
+}
+
+/// Like `runner::run_days`, but days whose answers for their data files are all cached are not
+/// run again.
+pub fn run_days(cache: &mut Cache, days: &[&Day], jobs: usize, budget: Budget) -> Vec<Report> {
+    // A data file that cannot be read is left for the runner to report.
+    let inputs: Vec<Option<Vec<u8>>> = days
+        .iter()
+        .map(|day| Source::File(day.data_path()).read().ok())
+        .collect();
+    let mut reports: Vec<Option<Report>> = days
+        .iter()
+        .zip(&inputs)
+        .map(|(day, input)| input.as_ref().and_then(|input| cache.lookup(day, input)))
+        .collect();
+
+    let missing: Vec<&Day> = days
+        .iter()
+        .zip(&reports)
+        .filter(|(_, report)| report.is_none())
+        .map(|(&day, _)| day)
+        .collect();
+    let mut fresh = runner::run_days(&missing, jobs, budget).into_iter();
+    for ((day, input), report) in days.iter().zip(&inputs).zip(reports.iter_mut()) {
+        if report.is_none() {
+            let new = fresh.next().expect("a report for every day run");
+            if let Some(input) = input {
+                cache.record(day, input, &new);
+            }
+            *report = Some(new);
+        }
+    }
+    reports.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+    use crate::testing::Scratch;
+
+    const EXAMPLE: &[u8] = b"1721\n979\n366\n299\n675\n1456\n";
+
+    #[test]
+    fn keys() {
+        let day1 = find_day("day1").unwrap();
+        let key = Key::new(day1, 2, EXAMPLE);
+        assert_eq!((key.day, key.part, key.version), (1, 2, day1.version));
+        assert_eq!(key, Key::new(day1, 2, EXAMPLE));
+        assert_ne!(key.input, Key::new(day1, 2, b"1721\n979\n").input);
+    }
+
+    #[test]
+    fn parse_and_print() {
+        let text = "# day part version input answer
+1 1 1 00000000000000ff 514579
+
+10 2 3 0123456789abcdef hello world
+";
+        let cache = Cache::parse(text).unwrap();
+        assert_eq!(cache.len(), 2);
+        let key = Key {
+            day: 10,
+            part: 2,
+            version: 3,
+            input: 0x0123_4567_89ab_cdef,
+        };
+        assert_eq!(cache.get(&key), Some(&Answer::parse("hello world")));
+        assert_eq!(cache.to_string(), text.replace("\n\n", "\n"));
+        assert!(!cache.changed());
+
+        let error = Cache::parse("1 1 1 xyz 5\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+        let error = Cache::parse("1 3 1 ff 5\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+    }
+
+    #[test]
+    fn old_versions_are_dropped() {
+        let mut cache = Cache::parse("1 1 1 ff 5\n1 1 2 ff 6\n2 1 1 ff 7\n99 1 1 ff 8\n").unwrap();
+        cache.retain_current(|day| match day {
+            1 => Some(2),
+            2 => Some(1),
+            _ => None,
+        });
+        assert_eq!(
+            cache.to_string().lines().skip(1).collect::<Vec<_>>(),
+            ["1 1 2 00000000000000ff 6", "2 1 1 00000000000000ff 7"]
+        );
+        assert!(cache.changed());
+    }
+
+    #[test]
+    fn answers_come_from_the_cache() {
+        let day1 = find_day("day1").unwrap();
+        let mut cache = Cache::default();
+        let report = run_day(&mut cache, day1, EXAMPLE, Budget::default());
+        assert_eq!(report.part1, Some(514579u64.into()));
+        assert!(cache.changed());
+        assert_eq!(cache.len(), 2);
+
+        // A cached answer is used as it is, without solving the day again.
+        cache.insert(Key::new(day1, 2, EXAMPLE), Answer::parse("cached"));
+        let report = run_day(&mut cache, day1, EXAMPLE, Budget::default());
+        assert_eq!(report.part2, Some(Answer::parse("cached")));
+
+        // Failed parts are not cached, so they are tried again next time.
+        let mut cache = Cache::default();
+        let report = run_day(&mut cache, day1, b"1\n2\n", Budget::default());
+        assert!(!report.succeeded());
+        assert!(cache.is_empty());
+
+        // Neither are parts that have not been solved yet.
+        cache.insert(Key::new(day1, 1, EXAMPLE), Answer::unsolved());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn save_load_and_clear() {
+        let scratch = Scratch::new("cache-files");
+        let path = scratch.join("nested").join("answers.txt");
+        assert!(Cache::load(&path).unwrap().is_empty());
+        assert_eq!(clear(&path).unwrap(), 0);
+
+        let day1 = find_day("day1").unwrap();
+        let mut cache = Cache::default();
+        run_day(&mut cache, day1, EXAMPLE, Budget::default());
+        cache.save(&path).unwrap();
+
+        let loaded = Cache::load(&path).unwrap();
+        assert_eq!(
+            loaded.lookup(day1, EXAMPLE).unwrap().part2,
+            Some(241861950u64.into())
+        );
+        assert!(!loaded.changed());
+        assert_eq!(clear(&path).unwrap(), 2);
+        assert!(!path.exists());
+    }
+}
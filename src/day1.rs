@@ -1,9 +1,23 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, Result};
+use crate::solver::Solver;
 use std::io;
 use std::io::BufRead;
 
-pub fn day1(expenses: &[usize]) {
-    println!("Part 1: {}", solve(expenses));
-    println!("Part 2: {}", solve_part2(expenses));
+pub struct Expenses(pub Vec<usize>);
+
+impl Solver for Expenses {
+    fn parse<IO: BufRead>(input: IO) -> Result<Self> {
+        Ok(Expenses(read_input(input)?))
+    }
+
+    fn part1(&self) -> Answer {
+        solve(&self.0).into()
+    }
+
+    fn part2(&self) -> Answer {
+        solve_part2_sorted(&self.0).into()
+    }
 }
 
 pub fn solve(expenses: &[usize]) -> usize {
@@ -17,6 +31,7 @@ pub fn solve(expenses: &[usize]) -> usize {
     panic!("no solution found");
 }
 
+/// Tries every triple. This is the reference `solve_part2_sorted` is checked against.
 pub fn solve_part2(expenses: &[usize]) -> usize {
     for i in 0..expenses.len() {
         for j in i + 1..expenses.len() {
@@ -30,12 +45,31 @@ pub fn solve_part2(expenses: &[usize]) -> usize {
     panic!("no solution found");
 }
 
-pub fn read_input<T: BufRead>(input: T) -> io::Result<Vec<usize>> {
-    let mut result = Vec::new();
-    for line in input.lines() {
-        result.push(line?.parse().unwrap());
+/// Finds the triple in O(n²) by walking two ends of the sorted expenses towards each other.
+pub fn solve_part2_sorted(expenses: &[usize]) -> usize {
+    let mut sorted = expenses.to_vec();
+    sorted.sort_unstable();
+    for (i, &a) in sorted.iter().enumerate() {
+        let (mut low, mut high) = (i + 1, sorted.len().saturating_sub(1));
+        while low < high {
+            let sum = a + sorted[low] + sorted[high];
+            if sum == 2020 {
+                return a * sorted[low] * sorted[high];
+            } else if sum < 2020 {
+                low += 1;
+            } else {
+                high -= 1;
+            }
+        }
     }
-    return Ok(result);
+    panic!("no solution found");
+}
+
+pub fn read_input<T: BufRead>(input: T) -> Result<Vec<usize>> {
+    let lines = input.lines().collect::<io::Result<Vec<_>>>()?;
+    Ok(parse_lines(lines.iter().map(String::as_str), |line| {
+        parse_number(line, line.trim())
+    })?)
 }
 
 #[cfg(test)]
@@ -47,6 +81,13 @@ mod test {
         assert!(solve(&[1721, 979, 366, 299, 675, 1456]) == 514579)
     }
 
+    #[test]
+    fn example_part2() {
+        let expenses = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(solve_part2(&expenses), 241861950);
+        assert_eq!(solve_part2_sorted(&expenses), 241861950);
+    }
+
     #[test]
     fn read_example() {
         let buffer = io::BufReader::new(
@@ -62,4 +103,16 @@ mod test {
         let result = solve(read_input(buffer).unwrap().as_slice());
         assert!(result == 514579);
     }
+
+    #[test]
+    fn read_bad_number() {
+        let buffer = io::BufReader::new("1721\n97x\n".as_bytes());
+        match read_input(buffer) {
+            Err(crate::error::Error::Parse(e)) => {
+                assert_eq!((e.line, e.column), (2, 1));
+                assert_eq!(e.text, "97x");
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
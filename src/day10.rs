@@ -1,17 +1,30 @@
+use crate::answer::Answer;
 use crate::day9::read_input;
+use crate::error::Result;
+use crate::solver::Solver;
+
+/// The adapter joltages, sorted.
+pub struct Adapters(pub Vec<usize>);
+
+impl Solver for Adapters {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        let mut adapters = read_input(lines.iter().map(String::as_str))?;
+        adapters.sort();
+        Ok(Adapters(adapters))
+    }
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let mut input = read_input(input.lines().map(|line| line.unwrap()));
-    input.sort();
-
-    println!("Part 1: {}", part1(input.as_slice()));
-    println!("Part 2: {}", part2(input.as_slice()));
+    fn part1(&self) -> Answer {
+        part1(self.0.as_slice()).into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        part2(self.0.as_slice()).into()
+    }
 }
 
 // input must be sorted
-fn part1(data: &[usize]) -> usize {
+pub fn part1(data: &[usize]) -> usize {
     let mut counts = [0usize; 4];
 
     data.iter().fold(0, |a, b| {
@@ -24,7 +37,7 @@ fn part1(data: &[usize]) -> usize {
 }
 
 // input must be sorted
-fn part2(data: &[usize]) -> usize {
+pub fn part2(data: &[usize]) -> usize {
     let mut counts = vec![0usize; data.len()];
 
     let mut i: isize = data.len() as isize - 1;
@@ -1,54 +1,74 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::{Grid, Position, DIRECTIONS};
+use crate::solver::Solver;
 use std::borrow::Borrow;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let floorplan = parse_board(input.lines().map(|line| line.unwrap()));
-    let fixpoint = to_fixpoint(&floorplan, |floorplan| floorplan.step());
-
-    println!("Part 1: {}", fixpoint.count_occupied());
+impl Solver for FloorPlan {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(parse_board(lines.iter().map(String::as_str))?)
+    }
 
-    let fixpoint = to_fixpoint(&floorplan, |floorplan| floorplan.step2());
-    println!("Part 2: {}", fixpoint.count_occupied());
+    fn part1(&self) -> Answer {
+        let fixpoint = to_fixpoint(self, |floorplan| floorplan.step());
+        fixpoint.count_occupied().into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        let fixpoint = to_fixpoint(self, |floorplan| floorplan.step2());
+        fixpoint.count_occupied().into()
+    }
 }
 
-fn parse_board<Lines: Iterator>(lines: Lines) -> FloorPlan
+pub fn parse_board<Lines: Iterator>(lines: Lines) -> std::result::Result<FloorPlan, ParseError>
 where
     Lines::Item: Borrow<str>,
 {
-    let seats = lines
-        .map(|line| {
-            line.borrow()
-                .chars()
-                .map(|c| match c {
-                    'L' => Seat::Empty,
-                    '.' => Seat::Floor,
-                    '#' => Seat::Occupied,
-                    other => panic!("Illegal character: {}", other),
-                })
-                .collect()
-        })
-        .collect();
-
-    FloorPlan { seats }
+    let seats = Grid::parse(lines, |c| match c {
+        'L' => Some(Seat::Empty),
+        '.' => Some(Seat::Floor),
+        '#' => Some(Seat::Occupied),
+        _ => None,
+    })?;
+
+    Ok(FloorPlan { seats })
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum Seat {
+/// A spot on the floor plan. Everything beyond the edges is floor.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Seat {
+    #[default]
     Floor,
     Empty,
     Occupied,
 }
 
-fn to_fixpoint<F>(floorplan: &FloorPlan, step: F) -> FloorPlan
+pub fn to_fixpoint<F>(floorplan: &FloorPlan, step: F) -> FloorPlan
 where
     F: Fn(&FloorPlan) -> (FloorPlan, bool),
 {
     let mut changed = true;
     let mut floorplan = floorplan;
     let mut floorplan_storage;
+    let mut generation = 0;
     while changed {
         let (floorplan_, changed_) = step(floorplan);
+        generation += 1;
+        // Some layouts flip between two states forever instead of settling.
+        crate::budget::spend(|| {
+            format!(
+                "generation {}, {} seats occupied",
+                generation,
+                floorplan_.count_occupied()
+            )
+        });
+        crate::explain!(
+            "generation",
+            number = generation,
+            occupied = floorplan_.count_occupied(),
+            changed = changed_,
+        );
         if !changed_ {
             return floorplan_;
         }
@@ -59,46 +79,32 @@ where
     unreachable!();
 }
 
-struct FloorPlan {
-    seats: Vec<Vec<Seat>>,
+pub struct FloorPlan {
+    seats: Grid<Seat>,
 }
 
 impl FloorPlan {
     fn step_by<F>(&self, step: F) -> (FloorPlan, bool)
     where
-        F: Fn(isize, isize) -> Seat,
+        F: Fn(Position, Seat) -> Seat,
     {
-        let mut changed = false;
-        let seats = (0..self.seats.len() as isize)
-            .map(|i| {
-                (0..self.seats[0].len() as isize)
-                    .map(|j| {
-                        let seat = self[(i, j)];
-                        let new_seat = step(i, j);
-                        if seat != new_seat {
-                            changed = true;
-                        }
-                        new_seat
-                    })
-                    .collect()
-            })
-            .collect();
-
+        let seats = self.seats.map(|position, &seat| step(position, seat));
+        let changed = seats != self.seats;
         (FloorPlan { seats }, changed)
     }
 
-    fn step(&self) -> (FloorPlan, bool) {
-        self.step_by(|i, j| match self[(i, j)] {
+    pub fn step(&self) -> (FloorPlan, bool) {
+        self.step_by(|position, seat| match seat {
             Seat::Floor => Seat::Floor,
             Seat::Empty => {
-                if self.count_neighbors(i, j) == 0 {
+                if self.count_neighbors(position) == 0 {
                     Seat::Occupied
                 } else {
                     Seat::Empty
                 }
             }
             Seat::Occupied => {
-                if self.count_neighbors(i, j) >= 4 {
+                if self.count_neighbors(position) >= 4 {
                     Seat::Empty
                 } else {
                     Seat::Occupied
@@ -107,18 +113,18 @@ impl FloorPlan {
         })
     }
 
-    fn step2(&self) -> (FloorPlan, bool) {
-        self.step_by(|i, j| match self[(i, j)] {
+    pub fn step2(&self) -> (FloorPlan, bool) {
+        self.step_by(|position, seat| match seat {
             Seat::Floor => Seat::Floor,
             Seat::Empty => {
-                if self.count_visible_neighbors(i, j) == 0 {
+                if self.count_visible_neighbors(position) == 0 {
                     Seat::Occupied
                 } else {
                     Seat::Empty
                 }
             }
             Seat::Occupied => {
-                if self.count_visible_neighbors(i, j) >= 5 {
+                if self.count_visible_neighbors(position) >= 5 {
                     Seat::Empty
                 } else {
                     Seat::Occupied
@@ -127,81 +133,37 @@ impl FloorPlan {
         })
     }
 
-    fn look(&self, (mut i, mut j): (isize, isize), (di, dj): (isize, isize)) -> usize {
-        i += di;
-        j += dj;
-
-        while self.inbounds(i, j) {
-            match self[(i, j)] {
-                Seat::Occupied => return 1,
-                Seat::Empty => return 0,
-                Seat::Floor => {
-                    i += di;
-                    j += dj;
-                }
-            }
-        }
-        0
-    }
-
-    fn inbounds(&self, i: isize, j: isize) -> bool {
-        !(i < 0 || j < 0 || i as usize >= self.seats.len() || j as usize >= self.seats[0].len())
-    }
-
-    fn count_neighbors(&self, i: isize, j: isize) -> usize {
-        self.count_neighbors_by(i, j, |(i, j), (di, dj)| match self[(i + di, j + dj)] {
-            Seat::Floor | Seat::Empty => 0,
-            Seat::Occupied => 1,
-        })
-    }
-
-    fn count_visible_neighbors(&self, i: isize, j: isize) -> usize {
-        self.count_neighbors_by(i, j, |x, dx| self.look(x, dx))
+    fn count_neighbors(&self, position: Position) -> usize {
+        self.seats
+            .neighbors(position)
+            .filter(|&&seat| seat == Seat::Occupied)
+            .count()
     }
 
-    fn count_neighbors_by<F>(&self, i: isize, j: isize, count: F) -> usize
-    where
-        F: Fn((isize, isize), (isize, isize)) -> usize,
-    {
-        (-1isize..=1)
-            .map(|di| {
-                (-1isize..=1)
-                    .map(|dj| {
-                        if di == 0 && dj == 0 {
-                            0
-                        } else {
-                            count((i, j), (di, dj))
-                        }
-                    })
-                    .fold(0, |a, b| a + b)
+    // Counts the directions in which the first seat in view is occupied.
+    fn count_visible_neighbors(&self, position: Position) -> usize {
+        DIRECTIONS
+            .iter()
+            .filter(|&&direction| {
+                self.seats
+                    .ray(position, direction)
+                    .find(|&&seat| seat != Seat::Floor)
+                    == Some(&Seat::Occupied)
             })
-            .fold(0, |a, b| a + b)
+            .count()
     }
 
-    fn count_occupied(&self) -> usize {
+    pub fn count_occupied(&self) -> usize {
         self.seats
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|seat| match seat {
-                        Seat::Occupied => 1,
-                        Seat::Empty | Seat::Floor => 0,
-                    })
-                    .fold(0, |a, b| a + b)
-            })
-            .fold(0, |a, b| a + b)
+            .cells()
+            .filter(|&&seat| seat == Seat::Occupied)
+            .count()
     }
 }
 
 impl std::fmt::Display for FloorPlan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.seats.as_slice() {
-            for c in row {
-                write!(f, "{}", c)?;
-            }
-            writeln!(f, "")?;
-        }
-        Ok(())
+        write!(f, "{}", self.seats)
     }
 }
 
@@ -215,18 +177,6 @@ impl std::fmt::Display for Seat {
     }
 }
 
-impl std::ops::Index<(isize, isize)> for FloorPlan {
-    type Output = Seat;
-
-    fn index(&self, (i, j): (isize, isize)) -> &Seat {
-        if !self.inbounds(i, j) {
-            &Seat::Floor
-        } else {
-            &self.seats[i as usize][j as usize]
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -244,7 +194,8 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
-        let floorplan = parse_board(example.lines());
+        let floorplan = parse_board(example.lines()).unwrap();
+        assert_eq!(floorplan.to_string(), example.to_string() + "\n");
         assert_eq!(
             to_fixpoint(&floorplan, |floorplan| floorplan.step()).count_occupied(),
             37
@@ -264,10 +215,25 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
-        let floorplan = parse_board(example.lines());
+        let floorplan = parse_board(example.lines()).unwrap();
         assert_eq!(
             to_fixpoint(&floorplan, |floorplan| floorplan.step2()).count_occupied(),
             26
         );
     }
+
+    #[test]
+    fn oscillation_runs_out_of_budget() {
+        // Every seat fills, then the middle four empty and refill, forever.
+        let floorplan = parse_board(".LL.\nLLLL\nLLLL\n.LL.".lines()).unwrap();
+        let budget = crate::budget::Budget {
+            steps: Some(100),
+            ..Default::default()
+        };
+        let exceeded = crate::budget::limit(budget, || {
+            to_fixpoint(&floorplan, |floorplan| floorplan.step()).count_occupied()
+        })
+        .unwrap_err();
+        assert_eq!(exceeded.progress, "generation 101, 12 seats occupied");
+    }
 }
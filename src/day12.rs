@@ -1,18 +1,30 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, ParseError, Result};
+use crate::solver::Solver;
 use std::borrow::Borrow;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let input = parse_directions(input.lines().map(|line| line.unwrap()));
+pub struct Directions(pub Vec<(char, isize)>);
 
-    let (n, e) = walk(input.as_slice());
-    println!("Part 1: {}", n.abs() + e.abs());
+impl Solver for Directions {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Directions(parse_directions(
+            lines.iter().map(String::as_str),
+        )?))
+    }
 
-    let (n, e) = walk_waypoint(input.as_slice());
-    println!("Part 1: {}", n.abs() + e.abs());
+    fn part1(&self) -> Answer {
+        let (n, e) = walk(self.0.as_slice());
+        (n.abs() + e.abs()).into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        let (n, e) = walk_waypoint(self.0.as_slice());
+        (n.abs() + e.abs()).into()
+    }
 }
 
-fn walk(directions: &[(char, isize)]) -> (isize, isize) {
+pub fn walk(directions: &[(char, isize)]) -> (isize, isize) {
     let (n, e, _dn, _de) = directions
         .iter()
         .fold((0, 0, 0, 1), |(n, e, dn, de), (dir, amount)| match dir {
@@ -34,7 +46,7 @@ fn walk(directions: &[(char, isize)]) -> (isize, isize) {
     (n, e)
 }
 
-fn walk_waypoint(directions: &[(char, isize)]) -> (isize, isize) {
+pub fn walk_waypoint(directions: &[(char, isize)]) -> (isize, isize) {
     let (n, e, _dn, _de) =
         directions
             .iter()
@@ -57,33 +69,44 @@ fn walk_waypoint(directions: &[(char, isize)]) -> (isize, isize) {
     (n, e)
 }
 
-fn rotate(dir: isize, n: isize, e: isize) -> (isize, isize) {
-    if dir == 90 || dir == -90 {
-        let sign = dir.signum();
-        (-sign * e, sign * n)
-    } else {
-        if dir > 0 {
-            let (n, e) = rotate(dir - 90, n, e);
-            rotate(90, n, e)
-        } else {
-            let (n, e) = rotate(dir + 90, n, e);
-            rotate(-90, n, e)
-        }
-    }
+/// Turns the vector (n, e) clockwise by `dir` degrees, which must be a multiple of 90.
+pub fn rotate(dir: isize, n: isize, e: isize) -> (isize, isize) {
+    // Only the direction we end up facing matters, not how many times we spun to get there.
+    (0..dir.rem_euclid(360) / 90).fold((n, e), |(n, e), _| (-e, n))
 }
 
-fn parse_directions<Lines: Iterator>(lines: Lines) -> Vec<(char, isize)>
+pub fn parse_directions<Lines: Iterator>(
+    lines: Lines,
+) -> std::result::Result<Vec<(char, isize)>, ParseError>
 where
     Lines::Item: Borrow<str>,
 {
-    lines.map(|line| parse_line(line.borrow())).collect()
+    parse_lines(lines, parse_line)
 }
 
-fn parse_line(line: &str) -> (char, isize) {
-    (
-        line.chars().nth(0).unwrap(),
-        line.split_at(1).1.parse().unwrap(),
-    )
+pub fn parse_line(line: &str) -> std::result::Result<(char, isize), LineError> {
+    let direction = match line.chars().next() {
+        Some(c) if "NSEWLRF".contains(c) => c,
+        Some(c) => {
+            return Err(LineError::character(
+                line,
+                0,
+                format!("illegal direction `{}`", c),
+            ))
+        }
+        None => return Err(LineError::whole(line, "expected a direction like `F10`")),
+    };
+    let amount_text = line[direction.len_utf8()..].trim_end();
+    let amount = parse_number(line, amount_text)?;
+    // rotate only knows how to turn in right angles
+    if (direction == 'L' || direction == 'R') && amount % 90 != 0 {
+        return Err(LineError::within(
+            line,
+            amount_text,
+            "turns must be a multiple of 90 degrees",
+        ));
+    }
+    Ok((direction, amount))
 }
 
 #[cfg(test)]
@@ -92,14 +115,21 @@ mod test {
 
     #[test]
     fn parse_lines() {
-        assert_eq!(parse_line("F10"), ('F', 10));
-        assert_eq!(parse_line("N3"), ('N', 3));
+        assert_eq!(parse_line("F10"), Ok(('F', 10)));
+        assert_eq!(parse_line("N3"), Ok(('N', 3)));
+    }
+
+    #[test]
+    fn parse_bad_lines() {
+        assert_eq!(parse_line("X10").unwrap_err().column, 1);
+        assert_eq!(parse_line("R45").unwrap_err().column, 2);
+        assert_eq!(parse_line("F1O").unwrap_err().text, "1O");
     }
 
     #[test]
     fn walk_example() {
         let directions = "F10\nN3\nF7\nR90\nF11";
-        let directions = parse_directions(directions.lines());
+        let directions = parse_directions(directions.lines()).unwrap();
         let (n, e) = walk(directions.as_slice());
         assert_eq!(n.abs() + e.abs(), 25);
     }
@@ -107,8 +137,19 @@ mod test {
     #[test]
     fn walk_waypoint_example() {
         let directions = "F10\nN3\nF7\nR90\nF11";
-        let directions = parse_directions(directions.lines());
+        let directions = parse_directions(directions.lines()).unwrap();
         let (n, e) = walk_waypoint(directions.as_slice());
         assert_eq!(n.abs() + e.abs(), 286);
     }
+
+    #[test]
+    fn rotations() {
+        assert_eq!(rotate(90, 1, 0), (0, 1));
+        assert_eq!(rotate(-90, 1, 0), (0, -1));
+        assert_eq!(rotate(270, 1, 0), rotate(-90, 1, 0));
+        assert_eq!(rotate(360, 3, 4), (3, 4));
+        assert_eq!(rotate(900000000, 3, 4), (3, 4));
+        assert_eq!(rotate(900000180, 3, 4), rotate(180, 3, 4));
+        assert_eq!(rotate(-900000090, 3, 4), rotate(-90, 3, 4));
+    }
 }
@@ -1,35 +1,68 @@
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let mut lines = input.lines();
-    let start = lines.next().unwrap()?.parse::<usize>().unwrap();
-    let busses: Vec<Option<usize>> = lines
-        .next()
-        .unwrap()?
+use crate::answer::Answer;
+use crate::error::{parse_number, LineError, Result};
+use crate::solver::Solver;
+
+pub struct Schedule {
+    pub start: usize,
+    pub busses: Vec<Option<usize>>,
+}
+
+impl Solver for Schedule {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let mut lines = input.lines();
+        let first = lines.next().transpose()?.unwrap_or_default();
+        let start = parse_number(&first, first.trim()).map_err(|e| e.at(1, &first))?;
+        let second = lines.next().transpose()?.unwrap_or_default();
+        let busses = parse_busses(&second).map_err(|e| e.at(2, &second))?;
+        Ok(Schedule { start, busses })
+    }
+
+    fn part1(&self) -> Answer {
+        let (best_id, best_wait) = self.earliest_bus();
+        (best_id * best_wait).into()
+    }
+
+    fn part2(&self) -> Answer {
+        self.earliest_alignment().into()
+    }
+}
+
+impl Schedule {
+    /// The first bus to leave after `start`, as `(id, wait_time)`.
+    pub fn earliest_bus(&self) -> (usize, usize) {
+        find_nearest(self.start, self.busses.as_slice())
+    }
+
+    /// The earliest time at which each bus leaves its offset after the first.
+    pub fn earliest_alignment(&self) -> usize {
+        find_consecutive(self.busses.as_slice())
+    }
+}
+
+pub fn parse_busses(line: &str) -> std::result::Result<Vec<Option<usize>>, LineError> {
+    line.trim()
         .split(',')
         .map(|bus| {
             if bus == "x" {
-                None
+                Ok(None)
             } else {
-                Some(bus.parse().unwrap())
+                match parse_number(line, bus)? {
+                    0 => Err(LineError::within(line, bus, "bus IDs must be positive")),
+                    id => Ok(Some(id)),
+                }
             }
         })
-        .collect();
-
-    let (best_id, best_wait) = find_nearest(start, busses.as_slice());
-
-    println!("Part 1: {}", best_id * best_wait);
-    println!("Part 2: {}", find_consecutive(busses.as_slice()));
-
-    Ok(())
+        .collect()
 }
 
 // returns (id, wait_time)
-fn find_nearest(start: usize, busses: &[Option<usize>]) -> (usize, usize) {
+pub fn find_nearest(start: usize, busses: &[Option<usize>]) -> (usize, usize) {
     busses
         .iter()
         .fold((0, usize::MAX), |(best_id, best_wait), bus| match bus {
             None => (best_id, best_wait),
             Some(id) => {
-                let wait = (start + id - 1) / id * id - start;
+                let wait = start.div_ceil(*id) * id - start;
                 if wait < best_wait {
                     (*id, wait)
                 } else {
@@ -39,15 +72,29 @@ fn find_nearest(start: usize, busses: &[Option<usize>]) -> (usize, usize) {
         })
 }
 
-fn find_consecutive(busses: &[Option<usize>]) -> usize {
+pub fn find_consecutive(busses: &[Option<usize>]) -> usize {
     let mut step = 1;
     let mut t = step;
     for (offset, bus) in busses.iter().enumerate() {
         if let Some(freq) = bus {
+            // If this bus shares a factor with an earlier one, it may never line up.
             while (t + offset) % freq != 0 {
+                crate::budget::spend(|| {
+                    format!(
+                        "aligning bus {} at offset {}, reached t = {}",
+                        freq, offset, t
+                    )
+                });
                 t += step;
             }
             step *= freq;
+            crate::explain!(
+                "align",
+                bus = freq,
+                offset = offset,
+                time = t,
+                period = step
+            );
         }
     }
     t
@@ -81,4 +128,35 @@ mod test {
 
         assert_eq!(find_consecutive(busses.as_slice()), 1068781);
     }
+
+    #[test]
+    fn parse_example() {
+        assert_eq!(
+            parse_busses("7,13,x,x,59,x,31,19"),
+            Ok(vec![
+                Some(7),
+                Some(13),
+                None,
+                None,
+                Some(59),
+                None,
+                Some(31),
+                Some(19)
+            ])
+        );
+        assert_eq!(parse_busses("7,y,13").unwrap_err().column, 3);
+        assert_eq!(parse_busses("7,0").unwrap_err().column, 3);
+    }
+
+    #[test]
+    fn shared_factor_runs_out_of_budget() {
+        // Multiples of 4 are even, so one after them is never a multiple of 6.
+        let budget = crate::budget::Budget {
+            steps: Some(1000),
+            ..Default::default()
+        };
+        let exceeded =
+            crate::budget::limit(budget, || find_consecutive(&[Some(4), Some(6)])).unwrap_err();
+        assert!(exceeded.progress.starts_with("aligning bus 6 at offset 1"));
+    }
 }
@@ -1,56 +1,84 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, ParseError, Result};
+use crate::solver::Solver;
 use regex::Regex;
 use std::borrow::Borrow;
 use std::collections::BTreeMap;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let program = parse_program(input.lines().map(|line| line.unwrap()));
+pub struct Program(pub Vec<Instruction>);
 
-    let mut machine = Machine::new();
-    machine.execute_program(program.iter());
-    let sum = machine.sum_memory();
-    println!("Part 1: {}", sum);
+impl Solver for Program {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Program(parse_program(lines.iter().map(String::as_str))?))
+    }
 
-    let mut machine = Machine::new();
-    machine.set_version2();
-    machine.execute_program(program.iter());
-    let sum = machine.sum_memory();
-    println!("Part 2: {}", sum);
+    fn part1(&self) -> Answer {
+        let mut machine = Machine::new();
+        machine.execute_program(self.0.iter());
+        machine.sum_memory().into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        let mut machine = Machine::new();
+        machine.set_version2();
+        machine.execute_program(self.0.iter());
+        machine.sum_memory().into()
+    }
 }
 
-fn parse_program<T: Iterator>(lines: T) -> Vec<Instruction>
+pub fn parse_program<T: Iterator>(lines: T) -> std::result::Result<Vec<Instruction>, ParseError>
 where
     T::Item: Borrow<str>,
 {
-    lines.map(|line| parse_instruction(line.borrow())).collect()
+    parse_lines(lines, parse_instruction)
 }
 
 #[derive(Debug, PartialEq)]
-enum Instruction {
+pub enum Instruction {
     Mask(String),
     Mem(u64, u64),
 }
 
-struct Machine {
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Mask(mask) => write!(f, "mask = {}", mask),
+            Instruction::Mem(address, value) => write!(f, "mem[{}] = {}", address, value),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Machine {
     mask: String,
     mem: BTreeMap<u64, u64>,
     version2: bool,
 }
 
 impl Machine {
-    fn new() -> Machine {
-        Machine {
-            mask: "".into(),
-            mem: BTreeMap::new(),
-            version2: false,
-        }
+    pub fn new() -> Machine {
+        Machine::default()
     }
 
-    fn set_version2(&mut self) {
+    pub fn set_version2(&mut self) {
         self.version2 = true;
     }
 
+    pub fn is_version2(&self) -> bool {
+        self.version2
+    }
+
+    /// The mask set by the last `mask` instruction, or an empty string before the first.
+    pub fn mask(&self) -> &str {
+        &self.mask
+    }
+
+    /// The value stored at `address`. Memory that was never written holds 0.
+    pub fn read(&self, address: u64) -> u64 {
+        self.mem.get(&address).copied().unwrap_or(0)
+    }
+
     fn execute(&mut self, i: &Instruction) {
         match i {
             Instruction::Mask(mask) => self.mask = mask.clone(),
@@ -66,31 +94,36 @@ impl Machine {
         match i {
             Instruction::Mask(mask) => self.mask = mask.clone(),
             Instruction::Mem(addr, value) => {
-                for_each_address(self.mask.as_str(), *addr, &mut |addr| {
+                for addr in floating_addresses(self.mask.as_str(), *addr) {
                     mem.insert(addr, *value);
-                })
+                }
             }
         }
     }
 
-    fn execute_program<'a, I: std::iter::Iterator<Item = &'a Instruction>>(&mut self, program: I) {
+    pub fn execute_program<'a, I: std::iter::Iterator<Item = &'a Instruction>>(
+        &mut self,
+        program: I,
+    ) {
         for i in program {
             if self.version2 {
                 self.execute2(i);
             } else {
-                self.execute(&i);
+                self.execute(i);
             }
         }
     }
 
-    fn sum_memory(&self) -> u64 {
+    pub fn sum_memory(&self) -> u64 {
         self.mem.values().sum()
     }
 }
 
-fn for_each_address<F: FnMut(u64)>(mask: &str, base: u64, f: &mut F) {
-    if let Some(x) = mask.chars().nth(0) {
-        let bit = 1 << mask.len() - 1;
+/// Decodes an address bit by bit, recursing on each floating bit. This is the reference
+/// `floating_addresses` is checked against.
+pub fn for_each_address<F: FnMut(u64)>(mask: &str, base: u64, f: &mut F) {
+    if let Some(x) = mask.chars().next() {
+        let bit = 1 << (mask.len() - 1);
         let rest = &mask[1..];
         match x {
             '0' => for_each_address(rest, base, f),
@@ -106,8 +139,22 @@ fn for_each_address<F: FnMut(u64)>(mask: &str, base: u64, f: &mut F) {
     }
 }
 
+/// The addresses `mask` decodes `base` to, found by counting down through the subsets of the
+/// floating bits instead of walking the mask once per address.
+pub fn floating_addresses(mask: &str, base: u64) -> impl Iterator<Item = u64> {
+    let (ones, zeros) = parse_mask(mask);
+    let floating = zeros & !ones;
+    let fixed = (base | ones) & !floating;
+    let mut subset = Some(floating);
+    std::iter::from_fn(move || {
+        let current = subset?;
+        subset = current.checked_sub(1).map(|next| next & floating);
+        Some(fixed | current)
+    })
+}
+
 // returns a tuple of the 1 mask and 0 mask
-fn parse_mask(mask: &str) -> (u64, u64) {
+pub fn parse_mask(mask: &str) -> (u64, u64) {
     mask.chars()
         .map(|c| match c {
             'X' => (0, 1),
@@ -120,25 +167,40 @@ fn parse_mask(mask: &str) -> (u64, u64) {
         })
 }
 
-fn parse_instruction(s: &str) -> Instruction {
+pub fn parse_instruction(s: &str) -> std::result::Result<Instruction, LineError> {
     lazy_static! {
-        static ref MASK: Regex = Regex::new(r"mask = (?P<mask>[X10]+)").unwrap();
-        static ref MEM: Regex = Regex::new(r"mem\[(?P<address>\d+)\] = (?P<value>\d+)").unwrap();
+        static ref MASK: Regex = Regex::new(r"^mask = (?P<mask>\S+)$").unwrap();
+        static ref MEM: Regex = Regex::new(r"^mem\[(?P<address>\d+)\] = (?P<value>\S+)$").unwrap();
     }
 
-    if let Some(mask) = MASK.captures(s) {
-        Instruction::Mask(mask["mask"].into())
-    } else if let Some(mem) = MEM.captures(s) {
-        Instruction::Mem(
-            mem["address"].parse().unwrap(),
-            mem["value"].parse().unwrap(),
-        )
+    let line = s.trim();
+    if let Some(captures) = MASK.captures(line) {
+        let mask = captures.name("mask").unwrap().as_str();
+        if let Some((i, c)) = mask.char_indices().find(|&(_, c)| !"X10".contains(c)) {
+            Err(LineError::within(
+                s,
+                &mask[i..i + c.len_utf8()],
+                format!("illegal mask bit `{}`", c),
+            ))
+        } else if mask.len() != 36 {
+            Err(LineError::within(s, mask, "masks must be 36 bits long"))
+        } else {
+            Ok(Instruction::Mask(mask.into()))
+        }
+    } else if let Some(captures) = MEM.captures(line) {
+        Ok(Instruction::Mem(
+            parse_number(s, captures.name("address").unwrap().as_str())?,
+            parse_number(s, captures.name("value").unwrap().as_str())?,
+        ))
     } else {
-        panic!("illegal instruction: {}", s);
+        Err(LineError::whole(
+            s,
+            "expected `mask = ...` or `mem[...] = ...`",
+        ))
     }
 }
 
-fn apply_mask((ones, zeros): (u64, u64), value: u64) -> u64 {
+pub fn apply_mask((ones, zeros): (u64, u64), value: u64) -> u64 {
     (value | ones) & zeros
 }
 
@@ -162,9 +224,22 @@ mod test {
     fn instruction_parse() {
         assert_eq!(
             parse_instruction("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X"),
-            Instruction::Mask("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".into())
+            Ok(Instruction::Mask(
+                "XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".into()
+            ))
+        );
+        assert_eq!(
+            parse_instruction("mem[7] = 101"),
+            Ok(Instruction::Mem(7, 101))
         );
-        assert_eq!(parse_instruction("mem[7] = 101"), Instruction::Mem(7, 101));
+    }
+
+    #[test]
+    fn bad_instruction_parse() {
+        assert_eq!(parse_instruction("mask = XX2X").unwrap_err().column, 10);
+        assert_eq!(parse_instruction("mask = XX1X").unwrap_err().text, "XX1X");
+        assert_eq!(parse_instruction("mem[7] = x").unwrap_err().column, 10);
+        assert_eq!(parse_instruction("nop").unwrap_err().column, 1);
     }
 
     #[test]
@@ -173,7 +248,7 @@ mod test {
             mem[8] = 11
             mem[7] = 101
             mem[8] = 0";
-        let program = parse_program(program.lines());
+        let program = parse_program(program.lines()).unwrap();
 
         let mut machine = Machine::new();
 
@@ -188,7 +263,7 @@ mod test {
         mem[42] = 100
         mask = 00000000000000000000000000000000X0XX
         mem[26] = 1";
-        let program = parse_program(program.lines());
+        let program = parse_program(program.lines()).unwrap();
 
         let mut machine = Machine::new();
         machine.set_version2();
@@ -201,7 +276,7 @@ mod test {
     fn exec_short_program() {
         let program = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
             mem[8] = 11";
-        let program = parse_program(program.lines());
+        let program = parse_program(program.lines()).unwrap();
 
         let mut machine = Machine::new();
 
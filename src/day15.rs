@@ -1,13 +1,37 @@
+use crate::answer::Answer;
+use crate::error::{parse_number, LineError, Result};
+use crate::solver::Solver;
 use std::collections::HashMap;
 use std::iter::FromIterator;
 
-const INPUT: &[usize] = &[6, 13, 1, 15, 2, 0];
+pub struct StartingNumbers(pub Vec<usize>);
 
-pub fn run() {
-    let last_spoken = play_game(INPUT, 2020);
-    println!("Part 1: {}", last_spoken);
+impl Solver for StartingNumbers {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let mut numbers = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            for number in line.split(',').filter(|n| !n.trim().is_empty()) {
+                numbers.push(parse_number(&line, number.trim()).map_err(|e| e.at(i + 1, &line))?);
+            }
+        }
+        if numbers.is_empty() {
+            return Err(
+                LineError::whole("", "expected starting numbers like `0,3,6`")
+                    .at(1, "")
+                    .into(),
+            );
+        }
+        Ok(StartingNumbers(numbers))
+    }
 
-    println!("Part 2: {}", play_game(INPUT, 30000000));
+    fn part1(&self) -> Answer {
+        play_game(self.0.as_slice(), 2020).into()
+    }
+
+    fn part2(&self) -> Answer {
+        play_game(self.0.as_slice(), 30000000).into()
+    }
 }
 
 struct Game {
@@ -44,7 +68,7 @@ impl Game {
     }
 }
 
-fn play_game(input: &[usize], turns: usize) -> usize {
+pub fn play_game(input: &[usize], turns: usize) -> usize {
     if turns - 1 < input.len() {
         input[turns - 1]
     } else {
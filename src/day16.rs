@@ -1,6 +1,8 @@
-use crate::util::Unwrap;
+use crate::answer::Answer;
+use crate::error::{parse_number, LineError, ParseError};
+use crate::records::{Record, Records};
+use crate::solver::Solver;
 use regex::Regex;
-use std::borrow::Borrow;
 use std::cmp::Ord;
 use std::cmp::Ordering;
 use std::cmp::Reverse;
@@ -12,16 +14,21 @@ use std::ops::Range;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let problem = Problem::parse::<_, String>(input.lines());
+impl Solver for Problem {
+    fn parse<IO: std::io::BufRead>(input: IO) -> crate::error::Result<Self> {
+        Problem::read(input)
+    }
 
-    println!("Part 1: {}", part1(&problem));
-    println!("Part 2: {}", part2(&problem));
+    fn part1(&self) -> Answer {
+        part1(self).into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        part2(self).into()
+    }
 }
 
-fn part1(problem: &Problem) -> usize {
+pub fn part1(problem: &Problem) -> usize {
     problem
         .nearby_tickets
         .iter()
@@ -33,7 +40,7 @@ fn part1(problem: &Problem) -> usize {
         .sum()
 }
 
-fn part2(problem: &Problem) -> usize {
+pub fn part2(problem: &Problem) -> usize {
     let mappings = find_mappings(problem);
 
     mappings
@@ -48,10 +55,10 @@ fn part2(problem: &Problem) -> usize {
         .sum()
 }
 
-struct Problem {
-    constraints: Vec<Constraint>,
-    my_ticket: Vec<usize>,
-    nearby_tickets: Vec<Vec<usize>>,
+pub struct Problem {
+    pub constraints: Vec<Constraint>,
+    pub my_ticket: Vec<usize>,
+    pub nearby_tickets: Vec<Vec<usize>>,
 }
 
 impl Problem {
@@ -59,7 +66,7 @@ impl Problem {
         &self,
     ) -> std::iter::Chain<
         std::iter::Once<&std::vec::Vec<usize>>,
-        std::slice::Iter<std::vec::Vec<usize>>,
+        std::slice::Iter<'_, std::vec::Vec<usize>>,
     > {
         let iter = std::iter::once(&self.my_ticket).chain(self.nearby_tickets.iter());
         iter
@@ -71,19 +78,8 @@ impl Problem {
             .collect()
     }
 
-    fn find_candidate_mappings(&self, field_id: usize) -> Vec<&Constraint> {
-        let valid = self.valid_tickets();
-        self.constraints
-            .iter()
-            .filter(|c| valid.iter().all(|t| c.matches(t[field_id])))
-            .collect()
-    }
-
-    fn count_possible_assignments(&self, constraint: &Constraint) -> usize {
-        self.find_candidate_assignments(constraint).len()
-    }
-
-    fn find_candidate_assignments(&self, constraint: &Constraint) -> Vec<usize> {
+    /// The fields of the valid tickets whose every value satisfies `constraint`.
+    pub fn find_candidate_assignments(&self, constraint: &Constraint) -> Vec<usize> {
         let valid = self.valid_tickets();
         self.fields_iter()
             .filter(|&i| valid.iter().all(|t| constraint.matches(t[i])))
@@ -98,109 +94,130 @@ impl Problem {
         self.constraints.iter().any(|c| c.matches(i))
     }
 
-    fn parse<T: Iterator, B: Borrow<str>>(mut lines: T) -> Problem
-    where
-        T::Item: Unwrap<B>,
-    {
-        let constraints = parse_constraints(&mut lines);
-
-        while let Some(line) = lines.next() {
-            if line.unwrap().borrow() == "your ticket:" {
-                break;
-            }
-        }
-        let my_ticket = lines
-            .next()
-            .unwrap()
-            .unwrap()
-            .borrow()
-            .split(",")
-            .map(|i| i.parse().unwrap())
-            .collect();
-        while let Some(line) = lines.next() {
-            if line.unwrap().borrow() == "nearby tickets:" {
-                break;
+    /// Reads the notes: the field rules, then the `your ticket:` and `nearby tickets:` sections.
+    pub fn read<IO: std::io::BufRead>(input: IO) -> crate::error::Result<Problem> {
+        let mut records = Records::new(input).with_sections(&[YOUR_TICKET, NEARBY_TICKETS]);
+        let mut constraints = Vec::new();
+        let mut my_ticket = None;
+        let mut nearby = None;
+        for record in records.by_ref() {
+            let record = record?;
+            match record.section.as_deref() {
+                None => constraints.extend(parse_constraints(&record)?),
+                Some(YOUR_TICKET) => {
+                    if my_ticket.is_none() {
+                        my_ticket = Some(match record.numbered().next() {
+                            Some((n, line)) => {
+                                parse_ticket(line, None).map_err(|e| e.at(n, line))?
+                            }
+                            None => {
+                                return Err(LineError::whole("", "expected your ticket")
+                                    .at(record.line, "")
+                                    .into())
+                            }
+                        });
+                    }
+                }
+                Some(_) => nearby.get_or_insert_with(Vec::new).push(record),
             }
         }
-        let nearby_tickets = lines
-            .map(|line| {
-                line.unwrap()
-                    .borrow()
-                    .split(",")
-                    .map(|i| i.parse().unwrap())
-                    .collect()
-            })
-            .collect();
 
-        Problem {
+        // A missing section is reported just past the end of the input.
+        let missing = |header: &str| {
+            LineError::whole("", format!("expected a `{}` section", header))
+                .at(records.lines_read() + 1, "")
+        };
+        let my_ticket = my_ticket.ok_or_else(|| missing(YOUR_TICKET))?;
+        let nearby_tickets = nearby
+            .ok_or_else(|| missing(NEARBY_TICKETS))?
+            .iter()
+            .flat_map(Record::numbered)
+            .map(|(n, line)| parse_ticket(line, Some(my_ticket.len())).map_err(|e| e.at(n, line)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Problem {
             constraints,
             my_ticket,
             nearby_tickets,
-        }
+        })
     }
 }
 
-fn parse_constraints<T: Iterator, S: Borrow<str>>(mut lines: T) -> Vec<Constraint>
-where
-    T::Item: Unwrap<S>,
-{
-    let mut constraints = Vec::new();
-    while let Some(line) = lines.next() {
-        let line = line.unwrap();
-        if line.borrow().is_empty() {
-            return constraints;
-        }
-        constraints.push(line.borrow().parse().unwrap())
+const YOUR_TICKET: &str = "your ticket:";
+const NEARBY_TICKETS: &str = "nearby tickets:";
+
+// Parses a comma-separated ticket, which must have `len` values if given.
+fn parse_ticket(line: &str, len: Option<usize>) -> Result<Vec<usize>, LineError> {
+    let ticket = line
+        .split(',')
+        .map(|i| parse_number(line, i))
+        .collect::<Result<Vec<_>, _>>()?;
+    match len {
+        Some(len) if ticket.len() != len => Err(LineError::whole(
+            line,
+            format!("expected {} values, found {}", len, ticket.len()),
+        )),
+        _ => Ok(ticket),
     }
-    constraints
+}
+
+fn parse_constraints(record: &Record) -> Result<Vec<Constraint>, ParseError> {
+    record
+        .numbered()
+        .map(|(n, line)| line.parse().map_err(|e: LineError| e.at(n, line)))
+        .collect()
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
-struct Constraint {
-    field_name: String,
-    ranges: [RangeInclusive<usize>; 2],
+pub struct Constraint {
+    pub field_name: String,
+    pub ranges: [RangeInclusive<usize>; 2],
 }
 
 impl Constraint {
-    fn matches(&self, i: usize) -> bool {
+    pub fn matches(&self, i: usize) -> bool {
         self.ranges.iter().any(|r| r.contains(&i))
     }
 }
 
 impl FromStr for Constraint {
-    type Err = <usize as std::str::FromStr>::Err;
+    type Err = LineError;
 
     fn from_str(s: &str) -> Result<Constraint, Self::Err> {
         lazy_static! {
             static ref RE: Regex = Regex::new(
-                r"(?P<field>[\w ]+): (?P<low1>\d+)-(?P<high1>\d+) or (?P<low2>\d+)-(?P<high2>\d+)"
+                r"^(?P<field>[\w ]+): (?P<low1>\d+)-(?P<high1>\d+) or (?P<low2>\d+)-(?P<high2>\d+)$"
             )
             .unwrap();
         }
-        if let Some(captures) = RE.captures(s) {
+        if let Some(captures) = RE.captures(s.trim_end()) {
+            let number = |name| parse_number(s, captures.name(name).unwrap().as_str());
             Ok(Constraint {
                 field_name: captures["field"].into(),
                 ranges: [
-                    (captures["low1"].parse()?)..=(captures["high1"].parse()?),
-                    (captures["low2"].parse()?)..=(captures["high2"].parse()?),
+                    (number("low1")?)..=(number("high1")?),
+                    (number("low2")?)..=(number("high2")?),
                 ],
             })
         } else {
-            "unmatched regex".parse()
+            Err(LineError::whole(
+                s,
+                "expected a field rule like `class: 1-3 or 5-7`",
+            ))
         }
     }
 }
 
-fn find_mappings(problem: &Problem) -> HashMap<&Constraint, usize> {
+pub fn find_mappings(problem: &Problem) -> HashMap<&Constraint, usize> {
     #[derive(PartialEq, Eq)]
     struct PendingConstraint<'a> {
         constraint: &'a Constraint,
         candidates: Vec<usize>,
-    };
+    }
 
     impl PartialOrd for PendingConstraint<'_> {
         fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-            self.candidates.len().partial_cmp(&other.candidates.len())
+            Some(self.cmp(other))
         }
     }
 
@@ -221,7 +238,11 @@ fn find_mappings(problem: &Problem) -> HashMap<&Constraint, usize> {
     let mut assigned_fields = HashSet::new();
 
     while let Some(c) = constraints.pop() {
-        println!("Assigning {}, candidates={:?}", c.0.constraint.field_name, c.0.candidates);
+        crate::explain!(
+            "assign",
+            field = c.0.constraint.field_name,
+            candidates = format!("{:?}", c.0.candidates),
+        );
         let remaining_candidates: Vec<&usize> =
             c.0.candidates
                 .iter()
@@ -241,22 +262,24 @@ mod test {
 
     #[test]
     fn part1_example() {
-        let problem = Problem::parse(EXAMPLE_INPUT.lines());
+        let problem = Problem::read(EXAMPLE_INPUT.as_bytes()).unwrap();
         assert_eq!(part1(&problem), 71);
     }
 
     #[test]
     fn example_mapping() {
-        let problem = Problem::parse(EXAMPLE_INPUT_2.lines());
-        let mut mapping = find_mappings(&problem)
+        let problem = Problem::read(EXAMPLE_INPUT_2.as_bytes()).unwrap();
+        let (mapping, steps) = crate::explain::capture(|| find_mappings(&problem));
+        assert_eq!(steps[0].to_string(), "assign field=seat candidates=[2]");
+        let mut mapping = mapping
             .iter()
             .map(|(c, &i)| (c.field_name.as_str(), i))
             .collect::<Vec<(&str, usize)>>();
-        mapping.sort_by(|(_, a), (_, b)| a.cmp(b));
+        mapping.sort_by_key(|&(_, i)| i);
         assert_eq!(mapping, vec![("row", 0), ("class", 1), ("seat", 2)]);
     }
 
-    const EXAMPLE_INPUT: &str = &"class: 1-3 or 5-7
+    const EXAMPLE_INPUT: &str = "class: 1-3 or 5-7
 row: 6-11 or 33-44
 seat: 13-40 or 45-50
 
@@ -269,7 +292,7 @@ nearby tickets:
 55,2,20
 38,6,12";
 
-    const EXAMPLE_INPUT_2: &str = &"class: 0-1 or 4-19
+    const EXAMPLE_INPUT_2: &str = "class: 0-1 or 4-19
 row: 0-5 or 8-19
 seat: 0-13 or 16-19
 
@@ -311,8 +334,13 @@ nearby tickets:
     #[test]
     fn parse_example_constraints() {
         assert_eq!(
-            parse_constraints(EXAMPLE_INPUT.lines()),
-            vec![
+            parse_constraints(
+                &Records::new(EXAMPLE_INPUT.as_bytes())
+                    .next()
+                    .unwrap()
+                    .unwrap()
+            ),
+            Ok(vec![
                 Constraint {
                     field_name: "class".into(),
                     ranges: [1..=3, 5..=7],
@@ -325,7 +353,35 @@ nearby tickets:
                     field_name: "seat".into(),
                     ranges: [13..=40, 45..=50],
                 }
-            ]
+            ])
         );
     }
+
+    fn parse_error(input: &str) -> ParseError {
+        match Problem::read(input.as_bytes()) {
+            Err(crate::error::Error::Parse(e)) => e,
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn parse_bad_input() {
+        assert_eq!(
+            "class: 1-3 or 5-x"
+                .parse::<Constraint>()
+                .unwrap_err()
+                .column,
+            1
+        );
+
+        let error = parse_error(&EXAMPLE_INPUT.replace("40,4,50", "40,4"));
+        assert_eq!(error.line, 10);
+
+        let error = parse_error(&EXAMPLE_INPUT.replace("7,1,14", "7,1,x"));
+        assert_eq!((error.line, error.column), (6, 5));
+
+        let error = parse_error(&EXAMPLE_INPUT.replace("nearby tickets:", "nearby:"));
+        assert_eq!(error.line, 13);
+        assert_eq!(error.message, "expected a `nearby tickets:` section");
+    }
 }
@@ -1,13 +1,17 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, Result};
+use crate::solver::Solver;
 use regex::Regex;
 
-struct Constraint {
+#[derive(Debug)]
+pub struct Constraint {
     min_count: usize,
     max_count: usize,
     character: char,
 }
 
 impl Constraint {
-    fn matches(&self, password: &str) -> bool {
+    pub fn matches(&self, password: &str) -> bool {
         let mut count = 0;
         for c in password.chars() {
             if c == self.character {
@@ -18,10 +22,10 @@ impl Constraint {
     }
 
     // Returns whether the password matches the interpretation of the password rules from Part 2.
-    fn matches2(&self, password: &str) -> bool {
+    pub fn matches2(&self, password: &str) -> bool {
         let mut count = 0;
         for (i, c) in password.char_indices() {
-            if (i+1 == self.min_count || i+1 == self.max_count) && c == self.character {
+            if (i + 1 == self.min_count || i + 1 == self.max_count) && c == self.character {
                 count += 1;
             }
         }
@@ -30,43 +34,62 @@ impl Constraint {
     }
 }
 
-fn parse_line(line: &str) -> (Constraint, String) {
+pub fn parse_line(line: &str) -> std::result::Result<(Constraint, String), LineError> {
     lazy_static! {
         static ref LINE_MATCHER: Regex =
-            Regex::new(r"(?P<min>\d+)-(?P<max>\d+) (?P<char>\w): (?P<password>\w+)").unwrap();
+            Regex::new(r"^(?P<min>\d+)-(?P<max>\d+) (?P<char>\w): (?P<password>\w+)$").unwrap();
     }
 
-    let captures = LINE_MATCHER.captures(line).unwrap();
-    let min = &captures["min"];
-    let max = &captures["max"];
+    let captures = match LINE_MATCHER.captures(line.trim()) {
+        Some(captures) => captures,
+        None => {
+            return Err(LineError::whole(
+                line,
+                "expected a policy and password like `1-3 a: abcde`",
+            ))
+        }
+    };
+    let min = captures.name("min").unwrap().as_str();
+    let max = captures.name("max").unwrap().as_str();
     let character = &captures["char"];
     let password = &captures["password"];
 
-    (
+    Ok((
         Constraint {
-            min_count: min.parse().unwrap(),
-            max_count: max.parse().unwrap(),
-            character: character.chars().nth(0).unwrap(),
+            min_count: parse_number(line, min)?,
+            max_count: parse_number(line, max)?,
+            character: character.chars().next().unwrap(),
         },
         password.to_string(),
-    )
+    ))
 }
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let mut valid_lines = 0;
-    let mut valid_lines2 = 0;
-    for line in input.lines() {
-        let (constraint, password) = parse_line(line?.as_str());
-        if constraint.matches(password.as_str()) {
-            valid_lines += 1;
-        }
-        if constraint.matches2(password.as_str()) {
-            valid_lines2 += 1;
-        }
+pub struct PasswordList(pub Vec<(Constraint, String)>);
+
+impl Solver for PasswordList {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(PasswordList(parse_lines(
+            lines.iter().map(String::as_str),
+            parse_line,
+        )?))
+    }
+
+    fn part1(&self) -> Answer {
+        self.0
+            .iter()
+            .filter(|(constraint, password)| constraint.matches(password.as_str()))
+            .count()
+            .into()
+    }
+
+    fn part2(&self) -> Answer {
+        self.0
+            .iter()
+            .filter(|(constraint, password)| constraint.matches2(password.as_str()))
+            .count()
+            .into()
     }
-    println!("Part 1: {}", valid_lines);
-    println!("Part 2: {}", valid_lines2);
-    Ok(())
 }
 
 #[cfg(test)]
@@ -74,27 +97,42 @@ mod test {
     #[test]
     fn parse_line() {
         let line = "1-3 a: abcde";
-        let (constraint, password) = super::parse_line(line);
+        let (constraint, password) = super::parse_line(line).unwrap();
         assert_eq!(constraint.min_count, 1);
         assert_eq!(constraint.max_count, 3);
         assert_eq!(constraint.character, 'a');
         assert_eq!(password, "abcde");
     }
 
+    #[test]
+    fn parse_bad_line() {
+        let error = super::parse_line("1-3 a abcde").unwrap_err();
+        assert_eq!(error.column, 1);
+        assert_eq!(error.text, "1-3 a abcde");
+    }
+
     #[test]
     fn test_matches() {
-        let cases = [("1-3 a: abcde", true), ("1-3 b: cdefg", false), ("2-9 c: ccccccccc", true)];
+        let cases = [
+            ("1-3 a: abcde", true),
+            ("1-3 b: cdefg", false),
+            ("2-9 c: ccccccccc", true),
+        ];
         for (line, expected) in &cases {
-            let (constraint, password) = super::parse_line(line);
+            let (constraint, password) = super::parse_line(line).unwrap();
             assert_eq!(constraint.matches(password.as_str()), *expected);
         }
     }
 
     #[test]
     fn test_matches2() {
-        let cases = [("1-3 a: abcde", true), ("1-3 b: cdefg", false), ("2-9 c: ccccccccc", false)];
+        let cases = [
+            ("1-3 a: abcde", true),
+            ("1-3 b: cdefg", false),
+            ("2-9 c: ccccccccc", false),
+        ];
         for (line, expected) in &cases {
-            let (constraint, password) = super::parse_line(line);
+            let (constraint, password) = super::parse_line(line).unwrap();
             assert_eq!(constraint.matches2(password.as_str()), *expected);
         }
     }
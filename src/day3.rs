@@ -1,58 +1,72 @@
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let map = parse_map(input)?;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::{Boundary, Grid};
+use crate::solver::Solver;
+use std::borrow::Borrow;
+
+impl Solver for Map {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(parse_map(lines.iter().map(String::as_str))?)
+    }
 
-    println!("Part 1: {}", count_trajectory(&map, 1, 3));
-    println!("Part 2: {}", part2(&map));
+    fn part1(&self) -> Answer {
+        count_trajectory(self, 1, 3).into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        part2(self).into()
+    }
 }
 
-fn part2(map: &Map) -> usize {
+pub fn part2(map: &Map) -> usize {
     [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
         .iter()
         .map(|(j, i)| count_trajectory(map, *i, *j))
-        .fold(1, |a, b| a * b)
+        .product()
 }
 
-fn parse_line(line: &str) -> Vec<bool> {
-    let mut result = Vec::new();
-
-    for c in line.chars() {
-        result.push(match c {
-            '.' => false,
-            '#' => true,
-            _ => panic!("unsupported character"),
-        })
-    }
-
-    result
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Square {
+    #[default]
+    Open,
+    Tree,
 }
 
-struct Map {
-    map: Vec<Vec<bool>>,
+impl std::fmt::Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Square::Open => write!(f, "."),
+            Square::Tree => write!(f, "#"),
+        }
+    }
 }
 
-impl std::ops::Index<(usize, usize)> for Map {
-    type Output = bool;
-
-    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
-        &self.map[i][j % self.map[i].len()]
-    }
+/// The slope, which repeats forever to the right.
+#[derive(Debug)]
+pub struct Map {
+    map: Grid<Square>,
 }
 
-fn parse_map<IO: std::io::BufRead>(input: IO) -> std::io::Result<Map> {
-    let mut map = Vec::new();
-    for line in input.lines() {
-        map.push(parse_line(line?.as_str()));
-    }
-    Ok(Map { map })
+pub fn parse_map<Lines: Iterator>(lines: Lines) -> std::result::Result<Map, ParseError>
+where
+    Lines::Item: Borrow<str>,
+{
+    let map = Grid::parse(lines, |c| match c {
+        '.' => Some(Square::Open),
+        '#' => Some(Square::Tree),
+        _ => None,
+    })?;
+    Ok(Map {
+        map: map.with_boundary(Boundary::WrapColumns),
+    })
 }
 
-fn count_trajectory(map: &Map, di: usize, dj: usize) -> usize {
+pub fn count_trajectory(map: &Map, di: usize, dj: usize) -> usize {
     let (mut i, mut j) = (0, 0);
     let mut count = 0;
-    while i < map.map.len() {
-        if map[(i, j)] {
+    while i < map.map.height() {
+        if map.map[(i as isize, j as isize)] == Square::Tree {
             count += 1
         }
         i += di;
@@ -67,14 +81,22 @@ mod test {
 
     #[test]
     fn parse_one_line() {
-        assert_eq!(
-            parse_line("..##......."),
-            vec![false, false, true, true, false, false, false, false, false, false, false]
-        );
+        let map = parse_map(["..##......."].iter().copied()).unwrap();
+        assert_eq!(map.map.width(), 11);
+        assert_eq!(map.map[(0, 2)], Square::Tree);
+        assert_eq!(map.map[(0, 4)], Square::Open);
+        assert_eq!(map.map[(0, 13)], Square::Tree);
+    }
+
+    #[test]
+    fn parse_unsupported_character() {
+        let error = parse_map(["..#O"].iter().copied()).unwrap_err();
+        assert_eq!(error.column, 4);
+        assert_eq!(error.text, "O");
     }
 
     fn example_map() -> Map {
-        let map = "..##.......
+        let example = "..##.......
 #...#...#..
 .#....#..#.
 ..#.#...#.#
@@ -86,7 +108,9 @@ mod test {
 #...##....#
 .#..#...#.#
 ";
-        parse_map(std::io::BufReader::new(map.as_bytes())).unwrap()
+        let map = parse_map(example.lines()).unwrap();
+        assert_eq!(map.map.to_string(), example);
+        map
     }
 
     #[test]
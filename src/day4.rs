@@ -1,47 +1,105 @@
+use crate::answer::Answer;
+use crate::error::{LineError, Result};
+use crate::records::Records;
+use crate::solver::Solver;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    println!("Part 2: {}", count_valid_passports(input)?);
-    Ok(())
+const REQUIRED_FIELDS: &[&str] = &["ecl", "pid", "eyr", "hcl", "byr", "iyr", "hgt"];
+
+pub struct Passports(pub Vec<String>);
+
+impl Solver for Passports {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        Ok(Passports(read_passports(input)?))
+    }
+
+    fn part1(&self) -> Answer {
+        self.0
+            .iter()
+            .filter(|passport| has_required_fields(passport))
+            .count()
+            .into()
+    }
+
+    fn part2(&self) -> Answer {
+        self.0
+            .iter()
+            .filter(|passport| parse_and_validate_passport(passport))
+            .count()
+            .into()
+    }
 }
 
-fn count_valid_passports<IO: std::io::BufRead>(input: IO) -> std::io::Result<usize> {
-    let mut passport = String::new();
-    let mut count = 0;
+pub fn read_passports<IO: std::io::BufRead>(input: IO) -> Result<Vec<String>> {
+    let mut passports = Vec::new();
+    for record in Records::new(input) {
+        let record = record?;
+        let mut seen_fields = HashSet::new();
+        for (n, line) in record.numbered() {
+            check_fields(line, &mut seen_fields).map_err(|e| e.at(n, line))?;
+        }
+        passports.push(record.text());
+    }
+    Ok(passports)
+}
 
-    for line in input.lines() {
-        let line = line?;
-        if line.trim() == "" {
-            if parse_and_validate_passport(passport.as_str()) {
-                count += 1
+// Checks that every field on the line is a `key:value` pair for a known key that has not already
+// appeared in this passport.
+fn check_fields(
+    line: &str,
+    seen_fields: &mut HashSet<String>,
+) -> std::result::Result<(), LineError> {
+    for field in line.split_whitespace() {
+        let key = match field.find(':') {
+            Some(i) if i > 0 && i + 1 < field.len() => &field[..i],
+            _ => {
+                return Err(LineError::within(
+                    line,
+                    field,
+                    "expected a field like `key:value`",
+                ))
             }
-            passport = String::new();
-        } else {
-            passport += line.as_str();
-            passport += "\n";
+        };
+        if key != "cid" && !REQUIRED_FIELDS.contains(&key) {
+            return Err(LineError::within(
+                line,
+                key,
+                format!("unrecognized field `{}`", key),
+            ));
+        }
+        if !seen_fields.insert(key.to_string()) {
+            return Err(LineError::within(
+                line,
+                key,
+                format!("duplicate field `{}`", key),
+            ));
         }
     }
+    Ok(())
+}
 
-    if passport != "" {
-        if parse_and_validate_passport(passport.as_str()) {
-            count += 1
-        }
+pub fn has_required_fields(passport: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?P<key>\w+):").unwrap();
     }
 
-    Ok(count)
+    let keys: HashSet<&str> = RE
+        .captures_iter(passport)
+        .map(|m| m.name("key").unwrap().as_str())
+        .collect();
+    REQUIRED_FIELDS.iter().all(|field| keys.contains(field))
 }
 
-fn parse_and_validate_passport(passport: &str) -> bool {
+pub fn parse_and_validate_passport(passport: &str) -> bool {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"(?P<key>\w+):(?P<value>[\S]+)").unwrap();
     }
 
-    let mut required_fields: HashMap<String, bool> =
-        ["ecl", "pid", "eyr", "hcl", "byr", "iyr", "hgt"]
-            .iter()
-            .map(|k| (k.to_string(), false))
-            .collect();
+    let mut required_fields: HashMap<String, bool> = REQUIRED_FIELDS
+        .iter()
+        .map(|k| (k.to_string(), false))
+        .collect();
 
     for m in RE.captures_iter(passport) {
         let key = &m["key"];
@@ -51,9 +109,10 @@ fn parse_and_validate_passport(passport: &str) -> bool {
             continue;
         }
 
+        // Unrecognized and duplicate fields are rejected when parsing, but treat them as invalid
+        // here too.
         match required_fields.insert(key.to_string(), true) {
-            None => panic!("no unrecognized fields allowed"),
-            Some(true) => panic!("no duplicate fields allowed"),
+            None | Some(true) => return false,
             Some(false) => (), // okay
         }
 
@@ -103,7 +162,7 @@ fn validate_year(y: &str, min: usize, max: usize) -> bool {
         return false;
     }
     match y.parse::<usize>() {
-        Ok(y) => min <= y && y <= max,
+        Ok(y) => (min..=max).contains(&y),
         Err(_) => false,
     }
 }
@@ -111,12 +170,12 @@ fn validate_year(y: &str, min: usize, max: usize) -> bool {
 fn validate_height(h: &str) -> bool {
     match h.strip_suffix("cm") {
         Some(h) => match h.parse::<usize>() {
-            Ok(h) => 150 <= h && h <= 193,
+            Ok(h) => (150..=193).contains(&h),
             Err(_) => false,
         },
         None => match h.strip_suffix("in") {
             Some(h) => match h.parse::<usize>() {
-                Ok(h) => 59 <= h && h <= 76,
+                Ok(h) => (59..=76).contains(&h),
                 Err(_) => false,
             },
             None => false,
@@ -154,6 +213,19 @@ mod test {
         ));
     }
 
+    #[test]
+    fn required_fields_example() {
+        assert!(has_required_fields(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+        byr:1937 iyr:2017 cid:147 hgt:183cm"
+        ));
+
+        assert!(!has_required_fields(
+            "iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+        hcl:#cfa07d byr:1929"
+        ));
+    }
+
     #[test]
     fn count_example_passports() {
         let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
@@ -170,9 +242,20 @@ mod test {
         hcl:#cfa07d eyr:2025 pid:166559648
         iyr:2011 ecl:brn hgt:59in";
 
-        assert_eq!(
-            count_valid_passports(std::io::BufReader::new(input.as_bytes())).unwrap(),
-            2
-        );
+        let passports = Passports::parse(std::io::BufReader::new(input.as_bytes())).unwrap();
+        assert_eq!(passports.part1(), "2");
+        assert_eq!(passports.part2(), "2");
+    }
+
+    #[test]
+    fn duplicate_field() {
+        let input = "ecl:gry pid:860033327\nbyr:1937 ecl:amb\n\necl:gry";
+        match Passports::parse(std::io::BufReader::new(input.as_bytes())) {
+            Err(crate::error::Error::Parse(e)) => {
+                assert_eq!((e.line, e.column), (2, 10));
+                assert_eq!(e.text, "ecl");
+            }
+            _ => panic!("expected a parse error"),
+        }
     }
 }
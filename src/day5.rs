@@ -1,41 +1,67 @@
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
+use crate::answer::Answer;
+use crate::error::{parse_lines, LineError, Result};
+use crate::solver::Solver;
+
+pub struct Seats(pub Vec<usize>);
+
+impl Solver for Seats {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        let seats = parse_lines(lines.iter().map(String::as_str), parse_seat_id)?;
+        // part1 takes the highest seat ID, so there has to be one.
+        if seats.is_empty() {
+            return Err(
+                LineError::whole("", "expected boarding passes like `FBFBBFFRLR`")
+                    .at(1, "")
+                    .into(),
+            );
+        }
+        Ok(Seats(seats))
+    }
+
+    fn part1(&self) -> Answer {
+        self.0.iter().copied().max().unwrap().into()
+    }
+
+    fn part2(&self) -> Answer {
+        find_missing(&self.0).into()
+    }
+}
+
+pub fn find_missing(ids: &[usize]) -> usize {
     let mut seats = [false; 1024];
-    println!(
-        "Part 1: {}",
-        input
-            .lines()
-            .map(|line| {
-                let id = parse_seat_id(line.unwrap().as_str());
-                seats[id] = true;
-                id
-            })
-            .max()
-            .unwrap()
-    );
+    for &id in ids {
+        seats[id] = true;
+    }
 
     let mut found_first = false;
-    let mut missing = 0;
     for (seat, &exists) in seats.iter().enumerate() {
         found_first |= exists;
         if found_first && !exists {
-            missing = seat;
-            break;
+            return seat;
         }
     }
-
-    println!("Part 2: {}", missing);
-
-    Ok(())
+    0
 }
 
-fn parse_seat_id(s: &str) -> usize {
-    s.chars()
-        .map(|c| match c {
-            'F' | 'L' => 0,
-            'B' | 'R' => 1,
-            _ => panic!("unexpected character"),
+pub fn parse_seat_id(s: &str) -> std::result::Result<usize, LineError> {
+    if s.chars().count() != 10 {
+        return Err(LineError::whole(
+            s,
+            "expected 10 characters like `FBFBBFFRLR`",
+        ));
+    }
+    s.char_indices()
+        .map(|(i, c)| match c {
+            'F' | 'L' => Ok(0),
+            'B' | 'R' => Ok(1),
+            _ => Err(LineError::character(
+                s,
+                i,
+                format!("unexpected character `{}`", c),
+            )),
         })
-        .fold(0, |a, b| (a << 1) + b)
+        .try_fold(0, |a, b| Ok((a << 1) + b?))
 }
 
 #[cfg(test)]
@@ -44,9 +70,29 @@ mod test {
 
     #[test]
     fn parse_seat_ids() {
-        assert_eq!(parse_seat_id("FBFBBFFRLR"), 357);
-        assert_eq!(parse_seat_id("BFFFBBFRRR"), 567);
-        assert_eq!(parse_seat_id("FFFBBBFRRR"), 119);
-        assert_eq!(parse_seat_id("BBFFBBFRLL"), 820);
+        assert_eq!(parse_seat_id("FBFBBFFRLR"), Ok(357));
+        assert_eq!(parse_seat_id("BFFFBBFRRR"), Ok(567));
+        assert_eq!(parse_seat_id("FFFBBBFRRR"), Ok(119));
+        assert_eq!(parse_seat_id("BBFFBBFRLL"), Ok(820));
+    }
+
+    #[test]
+    fn parse_bad_seat() {
+        assert_eq!(parse_seat_id("FBFBXFFRLR").unwrap_err().column, 5);
+        assert_eq!(parse_seat_id("FBF").unwrap_err().column, 1);
+    }
+
+    #[test]
+    fn parse_no_seats() {
+        let e = match Seats::parse(&b""[..]) {
+            Err(crate::error::Error::Parse(e)) => e,
+            _ => panic!("expected a parse error"),
+        };
+        assert_eq!(e.line, 1);
+    }
+
+    #[test]
+    fn find_missing_seat() {
+        assert_eq!(find_missing(&[3, 4, 6, 7]), 5);
     }
 }
@@ -1,26 +1,51 @@
+use crate::answer::Answer;
+use crate::error::{LineError, Result};
+use crate::records::Records;
+use crate::solver::Solver;
 use std::collections::HashSet;
 
-pub fn run<IO: std::io::Read>(mut input: IO) -> std::io::Result<()> {
-    let mut data = String::new();
-    input.read_to_string(&mut data)?;
+/// Each group's answers, one line per person.
+pub struct Groups(pub Vec<Vec<String>>);
 
-    println!("Part 1: {}", count_groups(data.as_str()));
-    println!("Part 2: {}", count_groups_all(data.as_str()));
+impl Solver for Groups {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let mut groups = Vec::new();
+        for record in Records::new(input) {
+            let record = record?;
+            for (n, line) in record.numbered() {
+                if let Some((j, c)) = line
+                    .char_indices()
+                    .find(|(_, c)| !c.is_ascii_lowercase() && !c.is_whitespace())
+                {
+                    return Err(LineError::character(
+                        line,
+                        j,
+                        format!("expected an answer from `a` to `z`, found `{}`", c),
+                    )
+                    .at(n, line)
+                    .into());
+                }
+            }
+            groups.push(record.lines);
+        }
+        Ok(Groups(groups))
+    }
+
+    fn part1(&self) -> Answer {
+        count_groups(&self.0).into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        count_groups_all(&self.0).into()
+    }
 }
 
-fn count_groups(input: &str) -> usize {
+pub fn count_groups(groups: &[Vec<String>]) -> usize {
     let mut count = 0;
-    let terminator = if input.find("\r\n").is_some() {
-        "\r\n\r\n"
-    } else {
-        "\n\n"
-    };
-    for group in input.split(terminator) {
+    for group in groups {
         let mut answers = std::collections::HashSet::new();
-        for c in group.chars() {
-            if 'a' <= c && c <= 'z' {
+        for c in group.iter().flat_map(|person| person.chars()) {
+            if c.is_ascii_lowercase() {
                 answers.insert(c);
             }
         }
@@ -29,22 +54,17 @@ fn count_groups(input: &str) -> usize {
     count
 }
 
-fn count_groups_all(input: &str) -> usize {
+pub fn count_groups_all(groups: &[Vec<String>]) -> usize {
     let mut count = 0;
     let all_answers = "abcdefghijklmnopqrstuvwxyz"
         .chars()
         .collect::<HashSet<char>>();
-    let terminator = if input.find("\r\n").is_some() {
-        "\r\n\r\n"
-    } else {
-        "\n\n"
-    };
-    for group in input.split(terminator) {
+    for group in groups {
         count += group
-            .lines()
+            .iter()
             .map(|person| person.chars().collect::<HashSet<char>>())
             .fold(all_answers.clone(), |a, b| {
-                a.intersection(&b).map(|&a| a).collect()
+                a.intersection(&b).copied().collect()
             })
             .len();
     }
@@ -55,14 +75,20 @@ fn count_groups_all(input: &str) -> usize {
 mod test {
     use super::*;
 
+    fn groups(input: &str) -> Vec<Vec<String>> {
+        Groups::parse(input.as_bytes()).unwrap().0
+    }
+
     #[test]
     fn count_groups_example() {
-        assert_eq!(count_groups(EXAMPLE_GROUPS), 11);
+        assert_eq!(count_groups(&groups(EXAMPLE_GROUPS)), 11);
+        assert_eq!(count_groups_all(&groups(EXAMPLE_GROUPS)), 6);
     }
 
     #[test]
     fn count_groups_example_win() {
-        assert_eq!(count_groups(EXAMPLE_GROUPS_WIN), 11);
+        assert_eq!(count_groups(&groups(EXAMPLE_GROUPS_WIN)), 11);
+        assert_eq!(count_groups_all(&groups(EXAMPLE_GROUPS_WIN)), 6);
     }
 
     static EXAMPLE_GROUPS: &str = "abc
@@ -81,7 +107,7 @@ a
 
 b";
 
-static EXAMPLE_GROUPS_WIN: &str = "abc\r
+    static EXAMPLE_GROUPS_WIN: &str = "abc\r
 \r
 a\r
 b\r
@@ -1,23 +1,40 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, ParseError, Result};
+use crate::solver::Solver;
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
 use std::iter::{FromIterator, Iterator};
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let graph = parse_graph(input.lines().map(|line| line.unwrap()));
+pub struct Rules(pub Graph);
 
-    println!("Part 1: {}", part1(&graph));
-    println!("Part 2: {}", part2(&graph));
-    Ok(())
+impl Solver for Rules {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Rules(parse_graph(lines.iter().map(String::as_str))?))
+    }
+
+    fn part1(&self) -> Answer {
+        part1(&self.0).into()
+    }
+
+    fn part2(&self) -> Answer {
+        part2(&self.0).into()
+    }
 }
 
-fn parse_graph<Lines: Iterator>(lines: Lines) -> Graph
+pub fn parse_graph<Lines: Iterator>(lines: Lines) -> std::result::Result<Graph, ParseError>
 where
     Lines::Item: Borrow<str>,
 {
-    lines.map(|line| parse_line(line.borrow())).collect()
+    Ok(parse_lines(lines, parse_line)?.into_iter().collect())
+}
+
+pub fn part1(graph: &Graph) -> usize {
+    holders(graph, "shiny gold").len()
 }
 
-fn part1(graph: &Graph) -> usize {
+/// Every bag that holds `bag`, directly or inside other bags.
+pub fn holders(graph: &Graph, bag: &str) -> HashSet<String> {
     // build the reverse graph
     let mut backedges = HashMap::<String, HashSet<String>>::new();
     for (name, edges) in graph {
@@ -34,61 +51,101 @@ fn part1(graph: &Graph) -> usize {
         }
     }
 
-    fn traverse<'a>(
+    fn traverse<'a: 'p, 'p>(
         backedges: &'a HashMap<String, HashSet<String>>,
-        edge: &String,
+        edge: &'p String,
+        path: &mut Vec<&'p String>,
     ) -> HashSet<&'a String> {
-        match backedges.get(edge) {
+        crate::budget::spend(|| format!("looking for bags that hold {}", edge));
+        check_acyclic(path, edge);
+        path.push(edge);
+        let result = match backedges.get(edge) {
             None => HashSet::new(),
             Some(edges) => edges
                 .iter()
-                .map(|edge| traverse(backedges, edge))
+                .map(|edge| traverse(backedges, edge, path))
                 .fold(HashSet::from_iter(edges), |a, b| {
-                    a.union(&b).map(|&x| x).collect()
+                    a.union(&b).copied().collect()
                 }),
-        }
+        };
+        path.pop();
+        result
     }
 
-    let transitive_closure = traverse(&backedges, &"shiny gold".to_string());
-    transitive_closure.len()
+    let transitive_closure = traverse(&backedges, &bag.to_string(), &mut vec![]);
+    transitive_closure.into_iter().cloned().collect()
+}
+
+pub fn part2(graph: &Graph) -> usize {
+    bags_inside(graph, "shiny gold")
 }
 
-fn part2(graph: &Graph) -> usize {
-    fn traverse(graph: &Graph, node: &String) -> usize {
+/// How many bags `bag` holds, counting the bags inside those too.
+pub fn bags_inside(graph: &Graph, bag: &str) -> usize {
+    fn traverse<'a>(graph: &'a Graph, node: &'a String, path: &mut Vec<&'a String>) -> usize {
+        crate::budget::spend(|| format!("counting the bags in {}", node));
+        check_acyclic(path, node);
+        path.push(node);
         let result = graph
             .get(node)
-            .unwrap_or(&vec![])
+            .map_or(&[][..], Vec::as_slice)
             .iter()
-            .map(|(count, edge)| count * traverse(graph, edge))
+            .map(|(count, edge)| count * traverse(graph, edge, path))
             .fold(1, |a, b| a + b);
+        path.pop();
         result
     }
     // -1 since we don't want to count the outermost bag
-    traverse(graph, &"shiny gold".to_string()) - 1
+    traverse(graph, &bag.to_string(), &mut vec![]) - 1
+}
+
+// A bag that holds itself, however indirectly, would have to be infinitely large, and following
+// the rules around the loop would never end.
+fn check_acyclic(path: &[&String], bag: &String) {
+    if let Some(start) = path.iter().position(|&outer| outer == bag) {
+        let cycle: Vec<&str> = path[start..].iter().map(|bag| bag.as_str()).collect();
+        panic!("the rules are cyclic: {} -> {}", cycle.join(" -> "), bag);
+    }
 }
 
-type Graph = HashMap<String, Vec<(usize, String)>>;
-type Node = (String, Vec<(usize, String)>);
+pub type Graph = HashMap<String, Vec<(usize, String)>>;
+pub type Node = (String, Vec<(usize, String)>);
 
-fn parse_line(line: &str) -> Node {
-    let mut parts = line.split(" bags contain ");
+pub fn parse_line(line: &str) -> std::result::Result<Node, LineError> {
+    let mut parts = line.trim().split(" bags contain ");
     let name = parts.next().unwrap();
-    let contents = parts.next().unwrap();
+    let contents = match parts.next() {
+        Some(contents) => contents,
+        None => {
+            return Err(LineError::whole(
+                line,
+                "expected a rule like `light red bags contain 1 bright white bag.`",
+            ))
+        }
+    };
     if contents == "no other bags." {
-        (name.to_string(), vec![])
+        Ok((name.to_string(), vec![]))
     } else {
-        (
+        Ok((
             name.to_string(),
             contents
                 .split(", ")
-                .map(|entry| {
-                    let mut parts = entry.split(" ");
-                    let count = parts.next().unwrap();
-                    let name = parts.next().unwrap().to_string() + " " + parts.next().unwrap();
-                    (count.parse().unwrap(), name)
-                })
-                .collect(),
-        )
+                .map(|entry| parse_entry(line, entry))
+                .collect::<std::result::Result<_, _>>()?,
+        ))
+    }
+}
+
+fn parse_entry(line: &str, entry: &str) -> std::result::Result<(usize, String), LineError> {
+    let mut parts = entry.split(' ');
+    let count = parse_number(line, parts.next().unwrap())?;
+    match (parts.next(), parts.next()) {
+        (Some(adjective), Some(color)) => Ok((count, adjective.to_string() + " " + color)),
+        _ => Err(LineError::within(
+            line,
+            entry,
+            "expected a count and a color like `2 muted yellow bags`",
+        )),
     }
 }
 
@@ -101,20 +158,20 @@ mod test {
         let line = "light red bags contain 1 bright white bag, 2 muted yellow bags.";
         assert_eq!(
             parse_line(line),
-            (
+            Ok((
                 "light red".to_string(),
                 vec![
                     (1, "bright white".to_string()),
                     (2, "muted yellow".to_string())
                 ]
-            )
+            ))
         );
     }
 
     #[test]
     fn parse_line_no_bags() {
         let line = "dotted black bags contain no other bags.";
-        assert_eq!(parse_line(line), ("dotted black".to_string(), vec![]));
+        assert_eq!(parse_line(line), Ok(("dotted black".to_string(), vec![])));
     }
 
     #[test]
@@ -122,13 +179,21 @@ mod test {
         let line = "bright white bags contain 1 shiny gold bag.";
         assert_eq!(
             parse_line(line),
-            (
+            Ok((
                 "bright white".to_string(),
                 vec![(1, "shiny gold".to_string())]
-            )
+            ))
         );
     }
 
+    #[test]
+    fn parse_line_bad_count() {
+        let line = "bright white bags contain one shiny gold bag.";
+        let error = parse_line(line).unwrap_err();
+        assert_eq!(error.column, 27);
+        assert_eq!(error.text, "one");
+    }
+
     #[test]
     fn traverse_example() {
         let graph = "light red bags contain 1 bright white bag, 2 muted yellow bags.
@@ -140,23 +205,34 @@ dark olive bags contain 3 faded blue bags, 4 dotted black bags.
 vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
-        let graph = parse_graph(graph.lines());
+        let graph = parse_graph(graph.lines()).unwrap();
 
         assert_eq!(part1(&graph), 4);
     }
 
     #[test]
     fn part2_example() {
-        let graph = 
-"shiny gold bags contain 2 dark red bags.
+        let graph = "shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
 dark yellow bags contain 2 dark green bags.
 dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
-        let graph = parse_graph(graph.lines());
+        let graph = parse_graph(graph.lines()).unwrap();
 
         assert_eq!(part2(&graph), 126);
     }
+
+    #[test]
+    #[should_panic(expected = "the rules are cyclic: shiny gold -> dull red -> shiny gold")]
+    fn cyclic_rules() {
+        let graph = parse_graph(
+            "shiny gold bags contain 2 dull red bags.
+dull red bags contain 1 shiny gold bag."
+                .lines(),
+        )
+        .unwrap();
+        part2(&graph);
+    }
 }
@@ -1,25 +1,32 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, ParseError, Result};
+use crate::solver::Solver;
 use std::borrow::Borrow;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let mut machine = parse_program(input.lines().map(|line| line.unwrap()));
+impl Solver for Machine {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(parse_program(lines.iter().map(String::as_str))?)
+    }
 
-    println!("Part 1: {}", machine.trace());
-    println!("Part 2: {}", fix_loop(&mut machine));
+    fn part1(&self) -> Answer {
+        self.clone().trace().into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        fix_loop(&mut self.clone()).into()
+    }
 }
 
-fn parse_program<Lines: Iterator>(lines: Lines) -> Machine
+pub fn parse_program<Lines: Iterator>(lines: Lines) -> std::result::Result<Machine, ParseError>
 where
     Lines::Item: Borrow<str>,
 {
-    let code = lines
-        .map(|line| Instruction::parse(line.borrow()))
-        .collect();
-    Machine::with_program(code)
+    let code = parse_lines(lines, Instruction::parse)?;
+    Ok(Machine::with_program(code))
 }
 
-fn fix_loop(machine: &mut Machine) -> isize {
+pub fn fix_loop(machine: &mut Machine) -> isize {
     machine.reset();
     let result = machine.trace();
     if machine.terminated() {
@@ -32,6 +39,11 @@ fn fix_loop(machine: &mut Machine) -> isize {
             machine.reset();
             let result = machine.trace();
             if machine.terminated() {
+                crate::explain!(
+                    "patch",
+                    address = i,
+                    instruction = machine.code[i].to_string(),
+                );
                 return result;
             }
             machine.code[i].toggle();
@@ -41,23 +53,40 @@ fn fix_loop(machine: &mut Machine) -> isize {
     panic!("could not fix loop");
 }
 
-#[derive(PartialEq, Debug)]
-struct Instruction {
-    opcode: &'static str,
-    argument: isize,
+#[derive(PartialEq, Debug, Clone)]
+pub struct Instruction {
+    pub opcode: &'static str,
+    pub argument: isize,
 }
 
 impl Instruction {
-    fn parse(s: &str) -> Instruction {
-        let mut parts = s.split(" ");
-        let opcode = match parts.next().unwrap() {
-            "nop" => "nop",
-            "acc" => "acc",
-            "jmp" => "jmp",
-            other => panic!("Unknown opcode: {}", other),
+    pub fn parse(s: &str) -> std::result::Result<Instruction, LineError> {
+        let mut parts = s.split_whitespace();
+        let opcode = match parts.next() {
+            Some("nop") => "nop",
+            Some("acc") => "acc",
+            Some("jmp") => "jmp",
+            Some(other) => {
+                return Err(LineError::within(
+                    s,
+                    other,
+                    format!("unknown opcode `{}`", other),
+                ))
+            }
+            None => return Err(LineError::whole(s, "expected an instruction")),
+        };
+        let argument = match parts.next() {
+            Some(argument) => parse_number(s, argument)?,
+            None => return Err(LineError::whole(s, "expected an argument")),
         };
-        let argument = parts.next().unwrap().parse().unwrap();
-        Instruction { opcode, argument }
+        if let Some(extra) = parts.next() {
+            return Err(LineError::within(
+                s,
+                extra,
+                "unexpected text after argument",
+            ));
+        }
+        Ok(Instruction { opcode, argument })
     }
 
     fn can_toggle(&self) -> bool {
@@ -73,14 +102,21 @@ impl Instruction {
     }
 }
 
-struct Machine {
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:+}", self.opcode, self.argument)
+    }
+}
+
+#[derive(Clone)]
+pub struct Machine {
     code: Vec<Instruction>,
     ip: isize,
     acc: isize,
 }
 
 impl Machine {
-    fn with_program(program: Vec<Instruction>) -> Machine {
+    pub fn with_program(program: Vec<Instruction>) -> Machine {
         Machine {
             code: program,
             ip: 0,
@@ -88,7 +124,7 @@ impl Machine {
         }
     }
 
-    fn step(&mut self) {
+    pub fn step(&mut self) {
         match &self.code[self.ip as usize] {
             Instruction {
                 opcode: "nop",
@@ -112,7 +148,7 @@ impl Machine {
         }
     }
 
-    fn trace(&mut self) -> isize {
+    pub fn trace(&mut self) -> isize {
         let mut visited = vec![false; self.code.len()];
         while !self.terminated() {
             let ip = self.ip as usize;
@@ -125,14 +161,32 @@ impl Machine {
         self.acc
     }
 
-    fn reset(&mut self) {
+    pub fn reset(&mut self) {
         self.ip = 0;
         self.acc = 0;
     }
 
-    fn terminated(&self) -> bool {
+    pub fn terminated(&self) -> bool {
         self.ip as usize == self.code.len()
     }
+
+    pub fn ip(&self) -> isize {
+        self.ip
+    }
+
+    pub fn acc(&self) -> isize {
+        self.acc
+    }
+
+    /// The instruction `step` would run next, or `None` if the instruction pointer is outside the
+    /// program.
+    pub fn next_instruction(&self) -> Option<&Instruction> {
+        if self.ip < 0 {
+            None
+        } else {
+            self.code.get(self.ip as usize)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -149,21 +203,28 @@ mod test {
     fn parse_instructions() {
         assert_eq!(
             Instruction::parse("nop +0"),
-            Instruction {
+            Ok(Instruction {
                 opcode: "nop",
                 argument: 0
-            }
+            })
         );
 
         assert_eq!(
             Instruction::parse("acc -99"),
-            Instruction {
+            Ok(Instruction {
                 opcode: "acc",
                 argument: -99
-            }
+            })
         );
     }
 
+    #[test]
+    fn parse_bad_instructions() {
+        assert_eq!(Instruction::parse("mul +2").unwrap_err().text, "mul");
+        assert_eq!(Instruction::parse("jmp x").unwrap_err().column, 5);
+        assert_eq!(Instruction::parse("acc").unwrap_err().column, 1);
+    }
+
     #[test]
     fn example_trace() {
         let program = "nop +0
@@ -175,7 +236,7 @@ acc -99
 acc +1
 jmp -4
 acc +6";
-        let mut machine = parse_program(program.lines());
+        let mut machine = parse_program(program.lines()).unwrap();
         assert_eq!(machine.trace(), 5);
     }
 
@@ -190,7 +251,10 @@ acc -99
 acc +1
 jmp -4
 acc +6";
-        let mut machine = parse_program(program.lines());
-        assert_eq!(fix_loop(&mut machine), 8);
+        let mut machine = parse_program(program.lines()).unwrap();
+        let (result, steps) = crate::explain::capture(|| fix_loop(&mut machine));
+        assert_eq!(result, 8);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].to_string(), "patch address=7 instruction=nop -4");
     }
 }
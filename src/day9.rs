@@ -1,23 +1,36 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, ParseError, Result};
+use crate::solver::Solver;
 use std::borrow::Borrow;
 
-pub fn run<IO: std::io::BufRead>(input: IO) -> std::io::Result<()> {
-    let input = read_input(input.lines().map(|line| line.unwrap()));
+pub struct Xmas(pub Vec<usize>);
 
-    let invalid = find_invalid(input.as_slice(), 25).unwrap();
-    println!("Part 1: {}", invalid);
-    println!("Part 2: {}", part2(input.as_slice(), invalid));
+impl Solver for Xmas {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Xmas(read_input(lines.iter().map(String::as_str))?))
+    }
+
+    fn part1(&self) -> Answer {
+        find_invalid(self.0.as_slice(), 25).unwrap().into()
+    }
 
-    Ok(())
+    fn part2(&self) -> Answer {
+        let invalid = find_invalid(self.0.as_slice(), 25).unwrap();
+        part2(self.0.as_slice(), invalid).into()
+    }
 }
 
-pub fn read_input<Lines: Iterator>(lines: Lines) -> Vec<usize>
+pub fn read_input<Lines: Iterator>(lines: Lines) -> std::result::Result<Vec<usize>, ParseError>
 where
     Lines::Item: Borrow<str>,
 {
-    lines.map(|line| line.borrow().parse().unwrap()).collect()
+    parse_lines(lines, |line| parse_number(line, line.trim()))
 }
 
-fn is_valid(previous: &[usize], number: usize) -> bool {
+/// Tries every pair, in O(w²). This is what `find_invalid` uses, and the reference
+/// `is_valid_sorted` is checked against.
+pub fn is_valid(previous: &[usize], number: usize) -> bool {
     for i in 0..previous.len() {
         for j in i + 1..previous.len() {
             if previous[i] != previous[j] && previous[i] + previous[j] == number {
@@ -28,7 +41,30 @@ fn is_valid(previous: &[usize], number: usize) -> bool {
     false
 }
 
-fn find_invalid(numbers: &[usize], window_size: usize) -> Option<usize> {
+/// Walks in from both ends of `sorted`, which must be in ascending order, in O(w) rather than
+/// O(w²). Nothing calls it yet: it is a candidate replacement for `is_valid`, kept as a target for
+/// the differential tests.
+pub fn is_valid_sorted(sorted: &[usize], number: usize) -> bool {
+    if sorted.is_empty() {
+        return false;
+    }
+    let (mut low, mut high) = (0, sorted.len() - 1);
+    while low < high {
+        let sum = sorted[low] + sorted[high];
+        if sum == number {
+            // Any other pair with this sum lies between the two, which are then all equal.
+            return sorted[low] != sorted[high];
+        }
+        if sum < number {
+            low += 1;
+        } else {
+            high -= 1;
+        }
+    }
+    false
+}
+
+pub fn find_invalid(numbers: &[usize], window_size: usize) -> Option<usize> {
     for window in numbers.windows(window_size + 1) {
         let last = window[window_size];
         if !is_valid(&window[0..window_size], last) {
@@ -38,7 +74,7 @@ fn find_invalid(numbers: &[usize], window_size: usize) -> Option<usize> {
     None
 }
 
-fn find_segment(numbers: &[usize], target: usize) -> &[usize] {
+pub fn find_segment(numbers: &[usize], target: usize) -> &[usize] {
     for i in 0..numbers.len() {
         let mut sum = numbers[i];
         for j in i + 1..numbers.len() {
@@ -56,7 +92,7 @@ fn find_segment(numbers: &[usize], target: usize) -> &[usize] {
     panic!("no solution found");
 }
 
-fn part2(numbers: &[usize], target: usize) -> usize {
+pub fn part2(numbers: &[usize], target: usize) -> usize {
     let sequence = find_segment(numbers, target);
     sequence.iter().min().unwrap() + sequence.iter().max().unwrap()
 }
@@ -92,7 +128,7 @@ mod test {
 277
 309
 576";
-        let sequence = read_input(example.lines());
+        let sequence = read_input(example.lines()).unwrap();
         assert_eq!(find_invalid(sequence.as_slice(), 5), Some(127));
     }
 
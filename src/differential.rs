@@ -0,0 +1,183 @@
+//! Differential testing: a brute-force reference and a faster replacement for the same function
+//! are run side by side on seeded random inputs, and the first input they disagree on is reported.
+//!
+//! A panic counts as an outcome like any other, so a replacement that panics where the reference
+//! returns, or the other way around, is a disagreement too.
+
+use crate::rng::Rng;
+use crate::runner::catch;
+use std::fmt;
+
+/// What one implementation did with an input: its output, or the message it panicked with.
+pub type Outcome<O> = std::result::Result<O, String>;
+
+/// The first input on which the two implementations gave different outcomes.
+#[derive(Debug)]
+pub struct Disagreement<I, O> {
+    /// The seed the inputs were generated from, to reproduce the run.
+    pub seed: u64,
+    /// Which input, counting from 0, they disagreed on.
+    pub case: usize,
+    pub input: I,
+    pub reference: Outcome<O>,
+    pub candidate: Outcome<O>,
+}
+
+impl<I: fmt::Debug, O: fmt::Debug> fmt::Display for Disagreement<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "case {} of seed {} disagrees on {:?}",
+            self.case, self.seed, self.input
+        )?;
+        writeln!(f, "  reference: {}", describe(&self.reference))?;
+        write!(f, "  candidate: {}", describe(&self.candidate))
+    }
+}
+
+fn describe<O: fmt::Debug>(outcome: &Outcome<O>) -> String {
+    match outcome {
+        Ok(output) => format!("{:?}", output),
+        Err(message) => format!("panicked: {}", message),
+    }
+}
+
+/// Runs `reference` and `candidate` on `cases` inputs drawn by `generate`, stopping at the first
+/// one where their outcomes differ. Panic messages are not compared, only whether each panicked.
+pub fn compare<I, O, G, R, C>(
+    seed: u64,
+    cases: usize,
+    mut generate: G,
+    reference: R,
+    candidate: C,
+) -> Result<(), Disagreement<I, O>>
+where
+    O: PartialEq,
+    G: FnMut(&mut Rng) -> I,
+    R: Fn(&I) -> O,
+    C: Fn(&I) -> O,
+{
+    let mut rng = Rng::new(seed);
+    for case in 0..cases {
+        let input = generate(&mut rng);
+        let expected = catch(|| reference(&input));
+        let actual = catch(|| candidate(&input));
+        let agree = match (&expected, &actual) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        };
+        if !agree {
+            return Err(Disagreement {
+                seed,
+                case,
+                input,
+                reference: expected,
+                candidate: actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{day1, day14, day9, generate};
+
+    fn check<I: fmt::Debug, O: fmt::Debug>(result: Result<(), Disagreement<I, O>>) {
+        if let Err(disagreement) = result {
+            panic!("{}", disagreement);
+        }
+    }
+
+    #[test]
+    fn reports_first_disagreement() {
+        let result = compare(
+            3,
+            100,
+            |rng| rng.below(10),
+            |&n| n * 2,
+            |&n| if n == 7 { 0 } else { n + n },
+        );
+        let disagreement = result.unwrap_err();
+        assert_eq!(disagreement.input, 7);
+        assert_eq!(disagreement.reference, Ok(14));
+        assert_eq!(disagreement.candidate, Ok(0));
+
+        let result = compare(3, 100, |rng| rng.below(10), |&n| 10 / n, |&n| 10 / n.max(1));
+        let disagreement = result.unwrap_err();
+        assert_eq!(disagreement.input, 0);
+        assert!(disagreement.reference.is_err());
+        assert!(disagreement.to_string().contains("reference: panicked"));
+    }
+
+    #[test]
+    fn expense_triples() {
+        // The generated expenses have exactly one triple, so any correct search finds the same one.
+        let expenses = |rng: &mut Rng| {
+            let input = generate::generate(1, rng.next_u64(), rng.between(5, 150)).unwrap();
+            day1::read_input(input.as_bytes()).unwrap()
+        };
+        check(compare(
+            1,
+            100,
+            expenses,
+            |e: &Vec<usize>| day1::solve_part2(e),
+            |e: &Vec<usize>| day1::solve_part2_sorted(e),
+        ));
+    }
+
+    #[test]
+    fn xmas_windows() {
+        // Small values make repeated numbers and valid pairs common.
+        let window = |rng: &mut Rng| {
+            let previous: Vec<usize> = (0..rng.between(0, 25)).map(|_| rng.below(40)).collect();
+            (previous, rng.below(80))
+        };
+        check(compare(
+            2,
+            2000,
+            window,
+            |(previous, n): &(Vec<usize>, usize)| day9::is_valid(previous, *n),
+            |(previous, n): &(Vec<usize>, usize)| {
+                let mut sorted = previous.clone();
+                sorted.sort_unstable();
+                day9::is_valid_sorted(&sorted, *n)
+            },
+        ));
+    }
+
+    #[test]
+    fn floating_addresses() {
+        let mask = |rng: &mut Rng| {
+            let mut floating = 0;
+            let mask: String = (0..36)
+                .map(|_| match rng.below(4) {
+                    0 if floating < 9 => {
+                        floating += 1;
+                        'X'
+                    }
+                    1 => '1',
+                    _ => '0',
+                })
+                .collect();
+            (mask, rng.next_u64() & ((1 << 36) - 1))
+        };
+        let sorted = |mut addresses: Vec<u64>| {
+            addresses.sort_unstable();
+            addresses
+        };
+        check(compare(
+            3,
+            500,
+            mask,
+            |(mask, base): &(String, u64)| {
+                let mut addresses = Vec::new();
+                day14::for_each_address(mask, *base, &mut |address| addresses.push(address));
+                sorted(addresses)
+            },
+            |(mask, base): &(String, u64)| sorted(day14::floating_addresses(mask, *base).collect()),
+        ));
+    }
+}
@@ -0,0 +1,404 @@
+//! A small line editor for interactive prompts, using nothing beyond std.
+//!
+//! On a terminal, `stty` puts it in raw mode so keys arrive one at a time, and the line is
+//! redrawn with ANSI escapes as it is edited. It understands the arrow keys, Home, End and
+//! Delete, the usual Emacs-style control keys, and Up and Down for earlier lines. When input is
+//! not a terminal, lines are read as they are.
+
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Reads lines, remembering them for recall with Up and Down.
+#[derive(Default)]
+pub struct Editor {
+    history: Vec<String>,
+}
+
+impl Editor {
+    pub fn new() -> Editor {
+        Editor::default()
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Reads a line from stdin, showing `prompt` first if stdin is a terminal. Returns `None` at
+    /// the end of the input.
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        if io::stdin().is_terminal() {
+            if let Some(raw) = RawMode::enter() {
+                let line = self.edit(&mut io::stdin().lock(), &mut io::stdout().lock(), prompt);
+                drop(raw);
+                return line;
+            }
+            // Without raw mode the terminal does its own, simpler, line editing.
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+    }
+
+    /// Edits a line read key by key from `input`, echoing it to `output` after `prompt`.
+    pub fn edit<R: Read, W: Write>(
+        &mut self,
+        input: &mut R,
+        output: &mut W,
+        prompt: &str,
+    ) -> io::Result<Option<String>> {
+        let mut line = Line {
+            chars: Vec::new(),
+            cursor: 0,
+        };
+        // Which history entry is shown, with `history.len()` for the line being written, and the
+        // line being written while an older one is shown.
+        let mut shown = self.history.len();
+        let mut draft = Vec::new();
+        loop {
+            line.draw(output, prompt)?;
+            let key = match read_key(input)? {
+                Some(key) => key,
+                None if line.chars.is_empty() => return Ok(None),
+                None => Key::Enter,
+            };
+            match key {
+                Key::Char(c) => {
+                    line.chars.insert(line.cursor, c);
+                    line.cursor += 1;
+                }
+                Key::Enter => {
+                    write!(output, "\r\n")?;
+                    output.flush()?;
+                    let text: String = line.chars.into_iter().collect();
+                    if !text.trim().is_empty() && self.history.last() != Some(&text) {
+                        self.history.push(text.clone());
+                    }
+                    return Ok(Some(text));
+                }
+                Key::Interrupt => {
+                    // Abandon the line, as a shell would.
+                    write!(output, "^C\r\n")?;
+                    line.chars.clear();
+                    line.cursor = 0;
+                    shown = self.history.len();
+                }
+                Key::Eof if line.chars.is_empty() => {
+                    write!(output, "\r\n")?;
+                    output.flush()?;
+                    return Ok(None);
+                }
+                Key::Eof | Key::Delete => {
+                    if line.cursor < line.chars.len() {
+                        line.chars.remove(line.cursor);
+                    }
+                }
+                Key::Backspace => {
+                    if line.cursor > 0 {
+                        line.cursor -= 1;
+                        line.chars.remove(line.cursor);
+                    }
+                }
+                Key::Left => line.cursor = line.cursor.saturating_sub(1),
+                Key::Right => line.cursor = (line.cursor + 1).min(line.chars.len()),
+                Key::Home => line.cursor = 0,
+                Key::End => line.cursor = line.chars.len(),
+                Key::KillLine => {
+                    line.chars.drain(..line.cursor);
+                    line.cursor = 0;
+                }
+                Key::KillToEnd => line.chars.truncate(line.cursor),
+                Key::KillWord => {
+                    let mut start = line.cursor;
+                    while start > 0 && line.chars[start - 1] == ' ' {
+                        start -= 1;
+                    }
+                    while start > 0 && line.chars[start - 1] != ' ' {
+                        start -= 1;
+                    }
+                    line.chars.drain(start..line.cursor);
+                    line.cursor = start;
+                }
+                Key::Up if shown > 0 => {
+                    if shown == self.history.len() {
+                        draft = line.chars.clone();
+                    }
+                    shown -= 1;
+                    line.show(self.history[shown].chars().collect());
+                }
+                Key::Down if shown < self.history.len() => {
+                    shown += 1;
+                    if shown == self.history.len() {
+                        line.show(std::mem::take(&mut draft));
+                    } else {
+                        line.show(self.history[shown].chars().collect());
+                    }
+                }
+                Key::Up | Key::Down | Key::Other => {}
+            }
+        }
+    }
+}
+
+struct Line {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl Line {
+    fn show(&mut self, chars: Vec<char>) {
+        self.cursor = chars.len();
+        self.chars = chars;
+    }
+
+    // Redraws the whole line and puts the cursor back where it belongs.
+    fn draw<W: Write>(&self, output: &mut W, prompt: &str) -> io::Result<()> {
+        let text: String = self.chars.iter().collect();
+        write!(output, "\r{}{}\x1b[K", prompt, text)?;
+        let back = self.chars.len() - self.cursor;
+        if back > 0 {
+            write!(output, "\x1b[{}D", back)?;
+        }
+        output.flush()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    /// Delete from the start of the line to the cursor.
+    KillLine,
+    /// Delete from the cursor to the end of the line.
+    KillToEnd,
+    /// Delete the word before the cursor.
+    KillWord,
+    Interrupt,
+    Eof,
+    Other,
+}
+
+// Reads one key press, which may take several bytes. Returns `None` at the end of the input.
+fn read_key<R: Read>(input: &mut R) -> io::Result<Option<Key>> {
+    let byte = match read_byte(input)? {
+        Some(byte) => byte,
+        None => return Ok(None),
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x02 => Key::Left,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Eof,
+        0x05 => Key::End,
+        0x06 => Key::Right,
+        0x0b => Key::KillToEnd,
+        0x0e => Key::Down,
+        0x10 => Key::Up,
+        0x15 => Key::KillLine,
+        0x17 => Key::KillWord,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Other,
+        byte => read_char(input, byte)?,
+    };
+    Ok(Some(key))
+}
+
+// Reads the rest of an escape sequence, such as `ESC [ A` for Up or `ESC [ 3 ~` for Delete.
+fn read_escape<R: Read>(input: &mut R) -> io::Result<Key> {
+    match read_byte(input)? {
+        Some(b'[') | Some(b'O') => {}
+        _ => return Ok(Key::Other),
+    }
+    let key = match read_byte(input)? {
+        Some(b'A') => Key::Up,
+        Some(b'B') => Key::Down,
+        Some(b'C') => Key::Right,
+        Some(b'D') => Key::Left,
+        Some(b'H') => Key::Home,
+        Some(b'F') => Key::End,
+        Some(digit) if digit.is_ascii_digit() => {
+            let mut code = vec![digit];
+            loop {
+                match read_byte(input)? {
+                    Some(b'~') => break,
+                    Some(byte) if byte.is_ascii_digit() || byte == b';' => code.push(byte),
+                    _ => return Ok(Key::Other),
+                }
+            }
+            match &code[..] {
+                b"1" | b"7" => Key::Home,
+                b"3" => Key::Delete,
+                b"4" | b"8" => Key::End,
+                _ => Key::Other,
+            }
+        }
+        _ => Key::Other,
+    };
+    Ok(key)
+}
+
+// Reads the rest of a UTF-8 character starting with `first`.
+fn read_char<R: Read>(input: &mut R, first: u8) -> io::Result<Key> {
+    let len = match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Ok(Key::Other),
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => return Ok(Key::Other),
+        }
+    }
+    Ok(match std::str::from_utf8(&bytes) {
+        Ok(text) => text.chars().next().map_or(Key::Other, Key::Char),
+        Err(_) => Key::Other,
+    })
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Keeps the terminal in raw mode, with no echo, until dropped.
+struct RawMode {
+    // The settings to restore, as printed by `stty -g`.
+    saved: String,
+}
+
+impl RawMode {
+    fn enter() -> Option<RawMode> {
+        let saved = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let saved = String::from_utf8(saved.stdout).ok()?.trim().to_string();
+        let status = Command::new("stty")
+            .args(["raw", "-echo"])
+            .stderr(Stdio::null())
+            .status()
+            .ok()?;
+        if status.success() {
+            Some(RawMode { saved })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty").arg(&self.saved).status();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edit(editor: &mut Editor, keys: &str) -> Option<String> {
+        let mut output = Vec::new();
+        editor
+            .edit(&mut keys.as_bytes(), &mut output, "> ")
+            .unwrap()
+    }
+
+    #[test]
+    fn typing_and_moving() {
+        let mut editor = Editor::new();
+        assert_eq!(edit(&mut editor, "acc\r"), Some("acc".to_string()));
+        // Left, then insert; Home and End; Backspace and Delete.
+        assert_eq!(
+            edit(&mut editor, "stp\x1b[De\x01x\x05y\x7f\x1b[D\x1b[3~\r"),
+            Some("xste".to_string())
+        );
+        assert_eq!(edit(&mut editor, "café\r"), Some("café".to_string()));
+    }
+
+    #[test]
+    fn killing() {
+        let mut editor = Editor::new();
+        assert_eq!(
+            edit(&mut editor, "contains shiny gold\x17\x17dark\r"),
+            Some("contains dark".to_string())
+        );
+        assert_eq!(
+            edit(&mut editor, "step 10\x01\x1b[C\x0b\r"),
+            Some("s".to_string())
+        );
+        assert_eq!(
+            edit(&mut editor, "step 10\x1b[D\x15\r"),
+            Some("0".to_string())
+        );
+    }
+
+    #[test]
+    fn history() {
+        let mut editor = Editor::new();
+        edit(&mut editor, "step\r");
+        edit(&mut editor, "acc\r");
+        edit(&mut editor, "acc\r");
+        edit(&mut editor, "  \r");
+        assert_eq!(editor.history(), ["step", "acc"]);
+
+        // Up goes back through the history, Down comes back to the line being written.
+        assert_eq!(
+            edit(&mut editor, "\x1b[A\x1b[A 5\r"),
+            Some("step 5".to_string())
+        );
+        assert_eq!(
+            edit(&mut editor, "mask\x1b[A\x1b[A\x1b[B\x1b[B\r"),
+            Some("mask".to_string())
+        );
+    }
+
+    #[test]
+    fn ending() {
+        let mut editor = Editor::new();
+        assert_eq!(edit(&mut editor, ""), None);
+        assert_eq!(edit(&mut editor, "\x04"), None);
+        // Ctrl-D deletes when there is something to delete, and Ctrl-C abandons the line.
+        assert_eq!(edit(&mut editor, "ab\x02\x04\r"), Some("a".to_string()));
+        assert_eq!(edit(&mut editor, "oops\x03acc\r"), Some("acc".to_string()));
+        // A last line without a newline still counts.
+        assert_eq!(edit(&mut editor, "acc"), Some("acc".to_string()));
+    }
+
+    #[test]
+    fn drawing() {
+        let mut editor = Editor::new();
+        let mut output = Vec::new();
+        editor
+            .edit(&mut &b"ab\x1b[D\r"[..], &mut output, "> ")
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("\r> ab\x1b[K\x1b[1D\r\n"), "{:?}", output);
+    }
+}
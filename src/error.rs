@@ -0,0 +1,222 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+/// Errors from reading and parsing a puzzle input.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(ParseError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Records which day's input this error came from, if it is a parse error.
+    pub fn for_day(self, day: usize) -> Error {
+        match self {
+            Error::Parse(e) => Error::Parse(ParseError {
+                day: Some(day),
+                ..e
+            }),
+            e => e,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A syntax error at a particular place in a puzzle input.
+///
+/// Lines and columns count from 1. Columns are in characters, not bytes.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub day: Option<usize>,
+    pub line: usize,
+    pub column: usize,
+    /// The text that could not be parsed.
+    pub text: String,
+    /// The whole line containing `text`.
+    pub source_line: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Renders the error the way a compiler would, pointing at `text` within its line.
+    ///
+    /// `source_name` names the input, such as its file path.
+    pub fn diagnostic(&self, source_name: &str) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let indent = " ".repeat(self.column - 1);
+        let underline = "^".repeat(self.text.chars().count().max(1));
+
+        let mut out = format!("error: {}\n", self.message);
+        out += &format!(
+            "{}--> {}:{}:{}\n",
+            gutter, source_name, self.line, self.column
+        );
+        out += &format!("{} |\n", gutter);
+        out += &format!("{} | {}\n", number, self.source_line);
+        out += &format!("{} | {}{}\n", gutter, indent, underline);
+        out
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day{} ", day)?;
+        }
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A syntax error within a single line, before we know which line that is.
+///
+/// Line-level parsers return this, and whatever is reading the input turns it into a
+/// `ParseError` with `at`.
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl LineError {
+    /// An error about the whole of `line`.
+    pub fn whole<M: Into<String>>(line: &str, message: M) -> LineError {
+        LineError {
+            column: 1,
+            text: line.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// An error about `part`, which should be a slice of `line`.
+    ///
+    /// If it is not, the error points at the start of the line.
+    pub fn within<M: Into<String>>(line: &str, part: &str, message: M) -> LineError {
+        let start = line.as_ptr() as usize;
+        let offset = (part.as_ptr() as usize).wrapping_sub(start);
+        let column = if offset <= line.len() && line.is_char_boundary(offset) {
+            line[..offset].chars().count() + 1
+        } else {
+            1
+        };
+        LineError {
+            column,
+            text: part.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// An error about the character starting at byte `index` of `line`.
+    pub fn character<M: Into<String>>(line: &str, index: usize, message: M) -> LineError {
+        let len = line[index..].chars().next().map_or(0, char::len_utf8);
+        LineError::within(line, &line[index..index + len], message)
+    }
+
+    pub fn at(self, line: usize, source_line: &str) -> ParseError {
+        ParseError {
+            day: None,
+            line,
+            column: self.column,
+            text: self.text,
+            source_line: source_line.to_string(),
+            message: self.message,
+        }
+    }
+}
+
+/// Parses every line with `parse`, numbering lines from 1 when reporting errors.
+pub fn parse_lines<Lines, T, F>(
+    lines: Lines,
+    mut parse: F,
+) -> std::result::Result<Vec<T>, ParseError>
+where
+    Lines: Iterator,
+    Lines::Item: Borrow<str>,
+    F: FnMut(&str) -> std::result::Result<T, LineError>,
+{
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.borrow();
+            parse(line).map_err(|e| e.at(i + 1, line))
+        })
+        .collect()
+}
+
+/// Parses `s` as a number, reporting failures against `line`.
+pub fn parse_number<T: std::str::FromStr>(
+    line: &str,
+    s: &str,
+) -> std::result::Result<T, LineError> {
+    s.parse()
+        .map_err(|_| LineError::within(line, s, format!("expected a number, found `{}`", s)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn column_within_line() {
+        let line = "acc +1";
+        let e = LineError::within(line, &line[4..], "bad");
+        assert_eq!(e.column, 5);
+        assert_eq!(e.text, "+1");
+    }
+
+    #[test]
+    fn numbers_lines() {
+        let e = parse_lines("1\n2\nx\n4".lines(), |line| {
+            parse_number::<usize>(line, line)
+        })
+        .unwrap_err();
+        assert_eq!(e.line, 3);
+        assert_eq!(e.column, 1);
+        assert_eq!(e.source_line, "x");
+    }
+
+    #[test]
+    fn render_diagnostic() {
+        let e = LineError::within("jmp +4", &"jmp +4"[4..], "bad argument").at(12, "jmp +4");
+        assert_eq!(
+            e.diagnostic("data/day8.txt"),
+            "error: bad argument
+  --> data/day8.txt:12:5
+   |
+12 | jmp +4
+   |     ^^
+"
+        );
+    }
+}
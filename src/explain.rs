@@ -0,0 +1,136 @@
+//! A sink for the intermediate steps solvers take, for `--explain`.
+//!
+//! Solvers report steps with the `explain!` macro. Nothing is recorded, or even formatted, unless
+//! a sink has been installed on the current thread with `with_sink` or `capture`, so solvers can
+//! explain themselves freely without slowing down or cluttering normal runs.
+
+use std::cell::RefCell;
+use std::fmt;
+
+/// One step a solver took, such as a field it assigned or an instruction it patched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+type Sink = Box<dyn FnMut(&Step)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = RefCell::new(None);
+}
+
+/// Records a step, e.g. `explain!("patch", address = i)`. Values are formatted with `Display`.
+#[macro_export]
+macro_rules! explain {
+    ($name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::explain::enabled() {
+            $crate::explain::record($crate::explain::Step {
+                name: $name,
+                fields: vec![$((stringify!($key), $value.to_string())),*],
+            });
+        }
+    };
+}
+
+/// Whether a sink is installed on this thread.
+pub fn enabled() -> bool {
+    SINK.with(|sink| sink.borrow().is_some())
+}
+
+/// Passes `step` to this thread's sink, if there is one.
+pub fn record(step: Step) {
+    SINK.with(|sink| {
+        if let Some(sink) = sink.borrow_mut().as_mut() {
+            sink(&step);
+        }
+    })
+}
+
+/// Runs `f` with `sink` receiving every step recorded on this thread.
+///
+/// The previous sink is put back afterwards, even if `f` panics.
+pub fn with_sink<T, S, F>(sink: S, f: F) -> T
+where
+    S: FnMut(&Step) + 'static,
+    F: FnOnce() -> T,
+{
+    struct Restore(Option<Sink>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SINK.with(|sink| *sink.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(SINK.with(|current| current.borrow_mut().replace(Box::new(sink))));
+    f()
+}
+
+/// Runs `f`, collecting the steps it records.
+pub fn capture<T, F: FnOnce() -> T>(f: F) -> (T, Vec<Step>) {
+    let steps = std::rc::Rc::new(RefCell::new(Vec::new()));
+    let sink = steps.clone();
+    let result = with_sink(move |step| sink.borrow_mut().push(step.clone()), f);
+    let steps = steps.borrow().clone();
+    (result, steps)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn silent_by_default() {
+        assert!(!enabled());
+        let mut formatted = false;
+        crate::explain!(
+            "never",
+            value = {
+                formatted = true;
+                1
+            }
+        );
+        assert!(!formatted);
+    }
+
+    #[test]
+    fn capture_steps() {
+        let (answer, steps) = capture(|| {
+            crate::explain!("assign", field = "row", index = 0);
+            42
+        });
+        assert_eq!(answer, 42);
+        assert_eq!(
+            steps,
+            vec![Step {
+                name: "assign",
+                fields: vec![("field", "row".to_string()), ("index", "0".to_string())],
+            }]
+        );
+        assert_eq!(steps[0].to_string(), "assign field=row index=0");
+        assert!(!enabled());
+    }
+
+    #[test]
+    fn nested_sinks() {
+        let (_, outer) = capture(|| {
+            crate::explain!("outer");
+            let (_, inner) = capture(|| crate::explain!("inner"));
+            assert_eq!(inner.len(), 1);
+            crate::explain!("outer");
+        });
+        assert_eq!(outer.len(), 2);
+    }
+}
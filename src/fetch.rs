@@ -0,0 +1,267 @@
+//! `fetch`: downloads a day's puzzle input into `data/`.
+//!
+//! Inputs are personal, so requests carry the session token from a file as a `session` cookie,
+//! the way the puzzle site expects. Only plain HTTP is spoken, so there is no default base URL: it
+//! has to point at something that serves plain HTTP, such as a local mirror or proxy, and the token
+//! is only sent to another machine if the config says that is intended.
+
+use crate::http::{self, Url};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The file the session token is read from by default.
+pub const DEFAULT_SESSION_FILE: &str = ".session";
+
+pub struct Config {
+    /// Where inputs come from. A day's input is at `/day/N/input` below it.
+    pub base_url: String,
+    pub session_file: PathBuf,
+    /// Send the session token to a host other than this machine, in the clear.
+    pub insecure: bool,
+    /// The directory inputs are saved in, as `dayN.txt`.
+    pub data_dir: PathBuf,
+    /// Download the input even if it has already been saved.
+    pub force: bool,
+    pub timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            base_url: String::new(),
+            session_file: DEFAULT_SESSION_FILE.into(),
+            insecure: false,
+            data_dir: "data".into(),
+            force: false,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// What `fetch` did.
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    /// The input was already saved here, so nothing was downloaded.
+    Cached(PathBuf),
+    /// The input was downloaded and saved here.
+    Downloaded(PathBuf),
+}
+
+/// Makes sure the input for `day` is saved, downloading it unless it already is.
+pub fn fetch(day: usize, config: &Config) -> io::Result<Fetched> {
+    let path = config.data_dir.join(format!("day{}.txt", day));
+    if !config.force && path.exists() {
+        return Ok(Fetched::Cached(path));
+    }
+
+    let url = session_url(&config.base_url, config.insecure)?;
+    let token = read_token(&config.session_file)?;
+    let cookie = format!("session={}", token);
+    let input_path = format!("/day/{}/input", day);
+    let response = http::send(
+        &url,
+        "GET",
+        &input_path,
+        &[("Cookie", &cookie)],
+        &[],
+        config.timeout,
+    )
+    .and_then(|response| http::expect_ok(&url, &input_path, response))?;
+
+    // Write next to the final file and move it into place, so an interrupted download never
+    // leaves a partial input that would be mistaken for a saved one.
+    fs::create_dir_all(&config.data_dir)?;
+    let partial = path.with_extension("txt.partial");
+    fs::write(&partial, &response.body)?;
+    fs::rename(&partial, &path)?;
+    Ok(Fetched::Downloaded(path))
+}
+
+/// Parses `base_url`, refusing it if the session token would go to another machine in the clear
+/// without `insecure`.
+pub(crate) fn session_url(base_url: &str, insecure: bool) -> io::Result<Url> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    if base_url.is_empty() {
+        return Err(invalid(
+            "no base URL; give one with --base-url that serves plain HTTP, such as a local proxy"
+                .to_string(),
+        ));
+    }
+    let url = Url::parse(base_url).map_err(invalid)?;
+    if !url.is_loopback() && !insecure {
+        return Err(invalid(format!(
+            "not sending the session token to {} unencrypted; use a local proxy, or --insecure \
+             to send it anyway",
+            url.host
+        )));
+    }
+    Ok(url)
+}
+
+pub(crate) fn read_token(path: &Path) -> io::Result<String> {
+    let token = fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!(
+                "could not read the session token from {}: {}",
+                path.display(),
+                e
+            ),
+        )
+    })?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has no session token in it", path.display()),
+        ));
+    }
+    Ok(token.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::http::Response;
+    use crate::testing::{stand_in, stand_in_on, Scratch};
+
+    // A scratch directory holding a session token.
+    fn with_token(name: &str) -> Scratch {
+        let scratch = Scratch::new(&format!("fetch-{}", name));
+        fs::write(scratch.join("session"), "abc123\n").unwrap();
+        scratch
+    }
+
+    fn config_for(scratch: &Scratch, url: &Url) -> Config {
+        Config {
+            base_url: url.to_string(),
+            session_file: scratch.join("session"),
+            insecure: false,
+            data_dir: scratch.join("data"),
+            force: false,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn download_once() {
+        let scratch = with_token("once");
+        let (url, requests) = stand_in(vec![
+            Response::new(200, "text/plain", "1721\n979\n"),
+            Response::new(200, "text/plain", "366\n"),
+        ]);
+        let mut config = config_for(&scratch, &url);
+        let path = config.data_dir.join("day1.txt");
+
+        assert_eq!(
+            fetch(1, &config).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/base/day/1/input");
+        assert_eq!(request.header("Cookie"), Some("session=abc123"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+
+        // The saved input is used without asking the server again...
+        assert_eq!(fetch(1, &config).unwrap(), Fetched::Cached(path.clone()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+
+        // ...unless forced.
+        config.force = true;
+        assert_eq!(
+            fetch(1, &config).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "366\n");
+        assert_eq!(requests.iter().count(), 1);
+    }
+
+    #[test]
+    fn server_errors_save_nothing() {
+        let scratch = with_token("errors");
+        let (url, _requests) = stand_in(vec![Response::new(
+            400,
+            "text/plain",
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+        )]);
+        let config = config_for(&scratch, &url);
+        let error = fetch(2, &config).unwrap_err().to_string();
+        assert!(
+            error.contains("answered 400 Bad Request: Puzzle inputs differ"),
+            "{}",
+            error
+        );
+        assert!(!config.data_dir.join("day2.txt").exists());
+    }
+
+    #[test]
+    fn token_stays_on_this_machine() {
+        let scratch = with_token("remote");
+        let mut config = Config {
+            base_url: "http://adventofcode.com/2020".to_string(),
+            ..config_for(&scratch, &Url::parse("http://127.0.0.1:9/").unwrap())
+        };
+        let error = fetch(4, &config).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(
+            error
+                .to_string()
+                .starts_with("not sending the session token to adventofcode.com unencrypted"),
+            "{}",
+            error
+        );
+
+        config.base_url = String::new();
+        let error = fetch(4, &config).unwrap_err().to_string();
+        assert!(error.starts_with("no base URL"), "{}", error);
+
+        for url in &[
+            "http://localhost:1/",
+            "http://127.0.0.1:1/",
+            "http://[::1]:1/",
+        ] {
+            assert!(session_url(url, false).is_ok(), "{}", url);
+        }
+        assert!(session_url("http://adventofcode.com/2020", true).is_ok());
+    }
+
+    #[test]
+    fn ipv6_loopback() {
+        let scratch = with_token("ipv6");
+        let (url, requests) = stand_in_on(
+            "[::1]:0",
+            vec![Response::new(200, "text/plain", "..#\n#..\n")],
+        );
+        let config = config_for(&scratch, &url);
+        assert!(config.base_url.starts_with("http://[::1]:"), "{}", url);
+        let path = config.data_dir.join("day3.txt");
+        assert_eq!(
+            fetch(3, &config).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "..#\n#..\n");
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/base/day/3/input");
+        assert_eq!(request.header("Host"), Some("[::1]"));
+    }
+
+    #[test]
+    fn needs_a_token() {
+        let scratch = with_token("token");
+        let url = Url::parse("http://127.0.0.1:9/").unwrap();
+        let mut config = config_for(&scratch, &url);
+        fs::write(&config.session_file, "  \n").unwrap();
+        let error = fetch(3, &config).unwrap_err().to_string();
+        assert!(error.contains("has no session token"), "{}", error);
+
+        config.session_file = scratch.join("missing");
+        let error = fetch(3, &config).unwrap_err().to_string();
+        assert!(
+            error.starts_with("could not read the session token"),
+            "{}",
+            error
+        );
+    }
+}
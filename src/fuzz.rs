@@ -0,0 +1,487 @@
+//! A mutation fuzzer for the input parsers.
+//!
+//! Each target is a parser fed with seed inputs, such as the data files and generated inputs,
+//! after a few random edits: bytes and lines deleted, duplicated, swapped or changed, and numbers
+//! made longer. A parser may reject anything it is given, but it must not panic, crash or fail to
+//! return promptly. Inputs that make it do any of those are failures, which can be saved to a
+//! corpus directory and replayed later.
+//!
+//! Inputs run in child processes when `Options::isolation` says how to start them, so that a
+//! parser that overflows its stack is reported rather than ending the run.
+
+use crate::hash::fnv1a;
+use crate::isolate::{self, Command, Exit};
+use crate::rng::Rng;
+use crate::solver::{Day, DAYS};
+use crate::{day12, day13, day14, day16, day2, day5, day7, day8};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+/// A parser to fuzz.
+#[derive(Clone)]
+pub struct Target {
+    pub name: String,
+    /// The day whose inputs make good seeds.
+    pub day: usize,
+    /// Whether the parser reads one line rather than a whole input.
+    pub line: bool,
+    parser: Parser,
+}
+
+#[derive(Clone, Copy)]
+enum Parser {
+    /// Parsers of text see invalid UTF-8 as replacement characters.
+    Text(fn(&str)),
+    Day(&'static Day),
+}
+
+impl Target {
+    fn text(name: &'static str, day: usize, line: bool, parse: fn(&str)) -> Target {
+        Target {
+            name: name.to_string(),
+            day,
+            line,
+            parser: Parser::Text(parse),
+        }
+    }
+
+    fn day(day: &'static Day) -> Target {
+        Target {
+            name: day.name(),
+            day: day.number,
+            line: false,
+            parser: Parser::Day(day),
+        }
+    }
+
+    fn parse(&self, input: &[u8]) {
+        match self.parser {
+            Parser::Text(parse) => parse(&String::from_utf8_lossy(input)),
+            Parser::Day(day) => {
+                let _ = day.parse(&mut &input[..]);
+            }
+        }
+    }
+
+    /// The name as a directory name: `day8::Instruction::parse` is `day8-Instruction-parse`.
+    pub fn slug(&self) -> String {
+        slug(&self.name)
+    }
+
+    /// Splits `inputs` into seeds the way this target reads them, one per line for line parsers.
+    pub fn seeds<I: IntoIterator<Item = Vec<u8>>>(&self, inputs: I) -> Vec<Vec<u8>> {
+        if !self.line {
+            return inputs.into_iter().collect();
+        }
+        inputs
+            .into_iter()
+            .flat_map(|input| {
+                input
+                    .split(|&b| b == b'\n')
+                    .filter(|line| !line.is_empty())
+                    .map(<[u8]>::to_vec)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Every parser that reads puzzle input: each line parser, then each day's whole-input parser.
+pub fn targets() -> Vec<Target> {
+    let mut targets = vec![
+        Target::text("day2::parse_line", 2, true, |input| {
+            let _ = day2::parse_line(input);
+        }),
+        Target::text("day5::parse_seat_id", 5, true, |input| {
+            let _ = day5::parse_seat_id(input);
+        }),
+        Target::text("day7::parse_line", 7, true, |input| {
+            let _ = day7::parse_line(input);
+        }),
+        Target::text("day8::Instruction::parse", 8, true, |input| {
+            let _ = day8::Instruction::parse(input);
+        }),
+        Target::text("day12::parse_line", 12, true, |input| {
+            let _ = day12::parse_line(input);
+        }),
+        Target::text("day13::parse_busses", 13, true, |input| {
+            let _ = day13::parse_busses(input);
+        }),
+        Target::text("day14::parse_instruction", 14, true, |input| {
+            let _ = day14::parse_instruction(input);
+        }),
+        Target::text("day16::Constraint::from_str", 16, true, |input| {
+            let _ = input.parse::<day16::Constraint>();
+        }),
+    ];
+    targets.extend(DAYS.iter().map(Target::day));
+    targets
+}
+
+/// Looks up a target by name.
+pub fn find_target(name: &str) -> Option<Target> {
+    targets().into_iter().find(|target| target.name == name)
+}
+
+/// The ways `mutate` can change an input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mutation {
+    DeleteByte,
+    DuplicateByte,
+    SwapBytes,
+    FlipByte,
+    DeleteLine,
+    DuplicateLine,
+    SwapLines,
+    GrowNumber,
+}
+
+impl Mutation {
+    pub const ALL: &'static [Mutation] = &[
+        Mutation::DeleteByte,
+        Mutation::DuplicateByte,
+        Mutation::SwapBytes,
+        Mutation::FlipByte,
+        Mutation::DeleteLine,
+        Mutation::DuplicateLine,
+        Mutation::SwapLines,
+        Mutation::GrowNumber,
+    ];
+
+    /// Applies the mutation at random positions. An empty input has a byte inserted instead.
+    pub fn apply(self, rng: &mut Rng, input: &mut Vec<u8>) {
+        if input.is_empty() {
+            input.push(*rng.choose(INTERESTING));
+            return;
+        }
+        match self {
+            Mutation::DeleteByte => {
+                input.remove(rng.below(input.len()));
+            }
+            Mutation::DuplicateByte => {
+                let i = rng.below(input.len());
+                input.insert(i, input[i]);
+            }
+            Mutation::SwapBytes => {
+                let (i, j) = (rng.below(input.len()), rng.below(input.len()));
+                input.swap(i, j);
+            }
+            Mutation::FlipByte => {
+                let i = rng.below(input.len());
+                input[i] = if rng.chance(1, 2) {
+                    input[i] ^ (1 << rng.below(8))
+                } else {
+                    *rng.choose(INTERESTING)
+                };
+            }
+            Mutation::DeleteLine | Mutation::DuplicateLine | Mutation::SwapLines => {
+                let mut lines: Vec<Vec<u8>> = input.split(|&b| b == b'\n').map(Vec::from).collect();
+                let i = rng.below(lines.len());
+                match self {
+                    Mutation::DeleteLine => {
+                        lines.remove(i);
+                    }
+                    Mutation::DuplicateLine => lines.insert(i, lines[i].clone()),
+                    _ => {
+                        let j = rng.below(lines.len());
+                        lines.swap(i, j);
+                    }
+                }
+                *input = lines.join(&b'\n');
+            }
+            Mutation::GrowNumber => {
+                // Appends digits to a number, or starts one if there are none, to find what
+                // trusts a number to be small.
+                let ends: Vec<usize> = (1..=input.len())
+                    .filter(|&i| {
+                        input[i - 1].is_ascii_digit()
+                            && input.get(i).is_none_or(|b| !b.is_ascii_digit())
+                    })
+                    .collect();
+                let end = match ends.as_slice() {
+                    [] => rng.below(input.len() + 1),
+                    ends => *rng.choose(ends),
+                };
+                for _ in 0..rng.between(1, 12) {
+                    input.insert(end, b'0' + rng.below(10) as u8);
+                }
+            }
+        }
+    }
+}
+
+/// Bytes that mean something to at least one parser.
+const INTERESTING: &[u8] = b"0123456789-+ :,.=\n\r\txXLFBRNSEW#abcdeyz[]";
+
+/// Applies one to four random mutations to `input`.
+pub fn mutate(rng: &mut Rng, input: &[u8]) -> Vec<u8> {
+    let mut output = input.to_vec();
+    for _ in 0..rng.between(1, 4) {
+        rng.choose(Mutation::ALL).apply(rng, &mut output);
+    }
+    output
+}
+
+/// What went wrong with an input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    Panic(String),
+    /// The parser took its process down, as by overflowing its stack. Only inputs run in a child
+    /// process can fail this way; otherwise the fuzzer goes down with it.
+    Crash(String),
+    /// The parser was still running when the time limit ran out.
+    Hang,
+}
+
+/// An input that made a target panic or hang.
+#[derive(Clone, Debug)]
+pub struct Failure {
+    pub target: String,
+    pub input: Vec<u8>,
+    pub problem: Problem,
+}
+
+impl Failure {
+    /// Writes the input to `corpus/<target>/<hash>.txt`, returning the path.
+    ///
+    /// The name is a hash of the contents, so saving the same input twice leaves one file.
+    pub fn save(&self, corpus: &Path) -> io::Result<PathBuf> {
+        let dir = corpus.join(slug(&self.target));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{:016x}.txt", fnv1a(&self.input)));
+        fs::write(&path, &self.input)?;
+        Ok(path)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Panic(message) => write!(f, "{} panicked: {}", self.target, message)?,
+            Problem::Crash(how) => write!(f, "{} crashed: {}", self.target, how)?,
+            Problem::Hang => write!(f, "{} hung", self.target)?,
+        }
+        write!(f, " on {:?}", String::from_utf8_lossy(&self.input))
+    }
+}
+
+fn slug(name: &str) -> String {
+    name.replace("::", "-")
+}
+
+/// Reads the inputs saved for `target` under `corpus`, if any.
+pub fn load_corpus(corpus: &Path, target: &Target) -> io::Result<Vec<Vec<u8>>> {
+    let dir = corpus.join(target.slug());
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+    paths.iter().map(fs::read).collect()
+}
+
+/// How long to fuzz, how long one input may take and where inputs run.
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub seed: u64,
+    /// The number of mutated inputs to try, on top of the seeds themselves.
+    pub iterations: usize,
+    pub timeout: Duration,
+    /// Starts a child process that parses its stdin with the target named by its last argument,
+    /// as `fuzz-child` does. Without one, inputs run on threads of this process.
+    pub isolation: Option<Command>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            seed: 1,
+            iterations: 1000,
+            timeout: Duration::from_secs(2),
+            isolation: None,
+        }
+    }
+}
+
+/// Runs `target` on each of `seeds` as given, then on mutations of them.
+///
+/// Only the first input to fail with each distinct problem is reported. Without isolation, a hang
+/// ends the run, since the thread running the parser cannot be stopped and is left behind.
+pub fn fuzz(target: &Target, seeds: &[Vec<u8>], options: &Options) -> Vec<Failure> {
+    let mut rng = Rng::new(options.seed);
+    let mut failures: Vec<Failure> = Vec::new();
+    let mutated = (0..options.iterations).map(|_| match seeds {
+        [] => mutate(&mut rng, b""),
+        seeds => {
+            let seed = rng.choose(seeds).clone();
+            mutate(&mut rng, &seed)
+        }
+    });
+    for input in seeds.iter().cloned().chain(mutated) {
+        let problem = match run(target, &input, options) {
+            None => continue,
+            Some(problem) => problem,
+        };
+        // What a crashed process printed differs from run to run, so crashes count as one problem.
+        let seen = |failure: &Failure| match (&failure.problem, &problem) {
+            (Problem::Crash(_), Problem::Crash(_)) => true,
+            (seen, problem) => seen == problem,
+        };
+        if failures.iter().any(seen) {
+            continue;
+        }
+        let hung = problem == Problem::Hang && options.isolation.is_none();
+        failures.push(Failure {
+            target: target.name.clone(),
+            input,
+            problem,
+        });
+        if hung {
+            break;
+        }
+    }
+    failures
+}
+
+/// Runs `target` on `input` in a child process or on its own thread, returning what went wrong, if
+/// anything.
+pub fn run(target: &Target, input: &[u8], options: &Options) -> Option<Problem> {
+    if let Some(command) = &options.isolation {
+        return match command.run(&[&target.name], input, options.timeout) {
+            Ok(Exit::Finished(_)) => None,
+            Ok(Exit::Panicked(message)) => Some(Problem::Panic(message)),
+            Ok(Exit::Crashed(how)) => Some(Problem::Crash(how)),
+            Ok(Exit::TimedOut) => Some(Problem::Hang),
+            Err(e) => Some(Problem::Crash(format!("could not start a child: {}", e))),
+        };
+    }
+    let (target, input) = (target.clone(), input.to_vec());
+    let receiver = match isolate::spawn(move || target.parse(&input)) {
+        Ok(receiver) => receiver,
+        Err(e) => return Some(Problem::Crash(format!("could not start a worker: {}", e))),
+    };
+    match receiver.recv_timeout(options.timeout) {
+        Ok(Ok(())) => None,
+        Ok(Err(message)) => Some(Problem::Panic(message)),
+        Err(RecvTimeoutError::Timeout) => Some(Problem::Hang),
+        Err(RecvTimeoutError::Disconnected) => Some(Problem::Crash("the worker died".to_string())),
+    }
+}
+
+/// The `fuzz-child` end of `Options::isolation`: parses `input` with the target called `name` on a
+/// thread of its own, returning the message it panicked with, if it did.
+pub fn child(name: &str, input: Vec<u8>) -> Result<(), String> {
+    let target = find_target(name).ok_or_else(|| format!("no fuzz target called {}", name))?;
+    isolate::on_thread(move || target.parse(&input))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate::generate;
+    use crate::testing::Scratch;
+
+    #[test]
+    fn mutations_change_input() {
+        let mut rng = Rng::new(5);
+        let input = b"ab\ncd\nef".to_vec();
+        for &mutation in Mutation::ALL {
+            let mut output = input.clone();
+            while output == input {
+                mutation.apply(&mut rng, &mut output);
+            }
+        }
+        let mut empty = Vec::new();
+        Mutation::DeleteByte.apply(&mut rng, &mut empty);
+        assert_eq!(empty.len(), 1);
+    }
+
+    #[test]
+    fn reports_panics_and_hangs() {
+        let target = Target::text("test::panics", 1, true, |input| {
+            assert!(!input.contains('!'), "bang");
+            while input.contains('?') {}
+        });
+        let options = Options {
+            iterations: 0,
+            timeout: Duration::from_millis(100),
+            ..Options::default()
+        };
+        let seeds = vec![b"ok".to_vec(), b"!".to_vec(), b"!!".to_vec(), b"?".to_vec()];
+        let failures = fuzz(&target, &seeds, &options);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].input, b"!");
+        assert!(matches!(&failures[0].problem, Problem::Panic(m) if m == "bang"));
+        assert_eq!(failures[1].problem, Problem::Hang);
+    }
+
+    #[test]
+    fn reports_crashes() {
+        let options = Options {
+            iterations: 20,
+            isolation: Some(Command::new("sh", &["-c", "cat >/dev/null; kill -ABRT $$"])),
+            ..Options::default()
+        };
+        let target = find_target("day2::parse_line").unwrap();
+        let seeds = vec![b"1-3 a: abcde".to_vec(), b"2-9 c: ccccccccc".to_vec()];
+        let failures = fuzz(&target, &seeds, &options);
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(&failures[0].problem, Problem::Crash(_)));
+        assert_eq!(failures[0].input, b"1-3 a: abcde");
+    }
+
+    #[test]
+    fn grow_numbers() {
+        let mut rng = Rng::new(3);
+        for _ in 0..20 {
+            let mut input = b"R90\nF7".to_vec();
+            Mutation::GrowNumber.apply(&mut rng, &mut input);
+            let text = String::from_utf8(input).unwrap();
+            assert!(
+                text.starts_with("R90") && text.contains("\nF7") && text.len() > 6,
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let scratch = Scratch::new("fuzz-corpus");
+        let corpus = scratch.path();
+        let failure = Failure {
+            target: "day8::Instruction::parse".to_string(),
+            input: b"jmp +".to_vec(),
+            problem: Problem::Hang,
+        };
+        let path = failure.save(corpus).unwrap();
+        assert_eq!(failure.save(corpus).unwrap(), path);
+        let target = find_target("day8::Instruction::parse").unwrap();
+        assert_eq!(
+            load_corpus(corpus, &target).unwrap(),
+            vec![b"jmp +".to_vec()]
+        );
+    }
+
+    #[test]
+    fn parsers_survive_mutations() {
+        let options = Options {
+            iterations: 300,
+            ..Options::default()
+        };
+        for target in targets() {
+            let inputs = (0..2).map(|seed| generate(target.day, seed, 10).unwrap().into_bytes());
+            let seeds = target.seeds(inputs);
+            let failures = fuzz(&target, &seeds, &options);
+            assert!(failures.is_empty(), "{}", failures[0]);
+        }
+    }
+}
@@ -0,0 +1,750 @@
+//! Synthetic puzzle inputs, for stress testing and benchmarking beyond the checked-in data.
+//!
+//! Every generator is deterministic for a given seed. `size` sets the scale of the input; what it
+//! counts depends on the day, and days whose answers would otherwise overflow cap it:
+//!
+//! | day | `size` is the number of...                     |
+//! |-----|------------------------------------------------|
+//! | 1   | expenses                                       |
+//! | 2   | password lines                                 |
+//! | 3   | rows of the tree map                           |
+//! | 4   | passports                                      |
+//! | 5   | boarding passes, at most 1000                  |
+//! | 6   | groups                                         |
+//! | 7   | bag colors, at least 3                         |
+//! | 8   | instructions                                   |
+//! | 9   | numbers, at most 1000                          |
+//! | 10  | adapters, at least 4                           |
+//! | 11  | rows of the seat layout                        |
+//! | 12  | navigation instructions                        |
+//! | 13  | bus slots, with buses added while they fit     |
+//! | 14  | memory writes                                  |
+//! | 15  | starting numbers, at most 10                   |
+//! | 16  | nearby tickets, with up to 20 fields           |
+
+use crate::day11::FloorPlan;
+use crate::rng::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Generates an input for `day`, or `None` if there is no generator for it.
+pub fn generate(day: usize, seed: u64, size: usize) -> Option<String> {
+    let generator: fn(&mut Rng, usize) -> String = match day {
+        1 => expenses,
+        2 => passwords,
+        3 => tree_map,
+        4 => passports,
+        5 => boarding_passes,
+        6 => answers,
+        7 => bag_rules,
+        8 => program,
+        9 => xmas,
+        10 => adapters,
+        11 => seat_layout,
+        12 => navigation,
+        13 => bus_schedule,
+        14 => mask_program,
+        15 => starting_numbers,
+        16 => tickets,
+        _ => return None,
+    };
+    // Mixing in the day keeps one seed from giving correlated inputs across days.
+    let mut rng = Rng::new(seed ^ (day as u64).wrapping_mul(0x2545_f491_4f6c_dd1d));
+    Some(generator(&mut rng, size.max(1)))
+}
+
+fn lines<I: IntoIterator<Item = String>>(lines: I) -> String {
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+// One pair and one triple sum to 2020. The filler is all over 1010, so no two filler values fit in
+// a pair or triple, and values that would complete one with the planted numbers are skipped.
+fn expenses(rng: &mut Rng, size: usize) -> String {
+    let planted = loop {
+        let a = rng.between(1011, 1500);
+        let (c, d) = (rng.between(300, 700), rng.between(300, 700));
+        let planted = [a, 2020 - a, c, d, 2020 - c - d];
+        let pairs = combinations(&planted, 2).filter(|sum| *sum == 2020).count();
+        let triples = combinations(&planted, 3).filter(|sum| *sum == 2020).count();
+        let distinct: HashSet<_> = planted.iter().collect();
+        if pairs == 1 && triples == 1 && distinct.len() == planted.len() {
+            break planted;
+        }
+    };
+
+    let excluded: HashSet<usize> = combinations(&planted, 1)
+        .chain(combinations(&planted, 2))
+        .filter(|&sum| sum < 2020)
+        .map(|sum| 2020 - sum)
+        .collect();
+    let mut expenses = planted.to_vec();
+    while expenses.len() < size.max(planted.len()) {
+        let n = rng.between(1011, 2019);
+        if !excluded.contains(&n) {
+            expenses.push(n);
+        }
+    }
+    rng.shuffle(&mut expenses);
+    lines(expenses.iter().map(|n| n.to_string()))
+}
+
+// The sums of every `k`-element combination of `items`.
+fn combinations(items: &[usize], k: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+    if k == 0 {
+        return Box::new(std::iter::once(0));
+    }
+    Box::new(
+        (0..items.len())
+            .flat_map(move |i| combinations(&items[i + 1..], k - 1).map(move |sum| sum + items[i])),
+    )
+}
+
+fn passwords(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        let min = rng.between(1, 5);
+        let max = rng.between(min + 1, min + 10);
+        let letter = (b'a' + rng.below(26) as u8) as char;
+        let length = rng.between(max, max + 8);
+        let password: String = (0..length)
+            .map(|_| {
+                if rng.chance(1, 3) {
+                    letter
+                } else {
+                    (b'a' + rng.below(26) as u8) as char
+                }
+            })
+            .collect();
+        format!("{}-{} {}: {}", min, max, letter, password)
+    }))
+}
+
+fn tree_map(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        (0..31)
+            .map(|_| if rng.chance(1, 4) { '#' } else { '.' })
+            .collect()
+    }))
+}
+
+const EYE_COLORS: &[&str] = &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+// Each passport has a random selection of fields, some with values that fail validation.
+fn passports(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    for i in 0..size {
+        if i > 0 {
+            output.push('\n');
+        }
+        let mut fields = Vec::new();
+        for &key in &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"] {
+            if rng.chance(1, 10) {
+                continue;
+            }
+            let valid = !rng.chance(1, 8);
+            let value = match (key, valid) {
+                ("byr", true) => rng.between(1920, 2002).to_string(),
+                ("iyr", true) => rng.between(2010, 2020).to_string(),
+                ("eyr", true) => rng.between(2020, 2030).to_string(),
+                ("byr", false) | ("iyr", false) | ("eyr", false) => {
+                    rng.between(1900, 2040).to_string()
+                }
+                ("hgt", true) if rng.chance(1, 2) => format!("{}cm", rng.between(150, 193)),
+                ("hgt", true) => format!("{}in", rng.between(59, 76)),
+                ("hgt", false) => rng.between(50, 200).to_string(),
+                ("hcl", true) => format!("#{:06x}", rng.below(1 << 24)),
+                ("hcl", false) => format!("{:06x}", rng.below(1 << 24)),
+                ("ecl", true) => rng.choose(EYE_COLORS).to_string(),
+                ("ecl", false) => "xry".to_string(),
+                ("pid", true) => format!("{:09}", rng.below(1_000_000_000)),
+                ("pid", false) => format!("{:08}", rng.below(100_000_000)),
+                _ => rng.between(100, 350).to_string(),
+            };
+            fields.push(format!("{}:{}", key, value));
+        }
+        rng.shuffle(&mut fields);
+        for (j, field) in fields.iter().enumerate() {
+            let separator = if j + 1 == fields.len() || rng.chance(1, 4) {
+                '\n'
+            } else {
+                ' '
+            };
+            write!(output, "{}{}", field, separator).unwrap();
+        }
+        if fields.is_empty() {
+            output += "cid:100\n";
+        }
+    }
+    output
+}
+
+// A contiguous block of seats with one missing from the middle.
+fn boarding_passes(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(3, 1000);
+    let first = rng.between(8, 1023 - count);
+    let missing = rng.between(first + 1, first + count - 2);
+    let mut ids: Vec<usize> = (first..first + count).filter(|&id| id != missing).collect();
+    rng.shuffle(&mut ids);
+    lines(ids.iter().map(|id| {
+        (0..10)
+            .map(|bit| {
+                let set = id & (1 << (9 - bit)) != 0;
+                match (bit < 7, set) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                }
+            })
+            .collect()
+    }))
+}
+
+fn answers(rng: &mut Rng, size: usize) -> String {
+    let groups: Vec<String> = (0..size)
+        .map(|_| {
+            lines((0..rng.between(1, 5)).map(|_| {
+                let mut letters: Vec<char> = ('a'..='z').collect();
+                rng.shuffle(&mut letters);
+                letters[..rng.between(1, 26)].iter().collect()
+            }))
+        })
+        .collect();
+    groups.join("\n")
+}
+
+const ADJECTIVES: &[&str] = &[
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+];
+const COLORS: &[&str] = &[
+    "aqua", "beige", "black", "blue", "bronze", "brown", "coral", "crimson", "cyan", "fuchsia",
+    "gold", "gray", "green", "indigo", "lavender", "lime", "magenta", "maroon", "olive", "orange",
+    "plum", "purple", "red", "salmon", "silver", "tan", "teal", "tomato", "violet", "white",
+    "yellow",
+];
+
+// Bags only contain bags that come later in a random order, so the rules cannot form a cycle.
+// Shiny gold is placed near the end so the count of bags inside it stays small, but never first or
+// last, so there are always at least three bags.
+fn bag_rules(rng: &mut Rng, size: usize) -> String {
+    let mut names: Vec<String> = ADJECTIVES
+        .iter()
+        .flat_map(|adjective| {
+            COLORS
+                .iter()
+                .map(move |color| format!("{} {}", adjective, color))
+        })
+        .filter(|name| name != "shiny gold")
+        .collect();
+    rng.shuffle(&mut names);
+    names.truncate(size.clamp(3, names.len()) - 1);
+    let gold = names.len().saturating_sub(rng.between(4, 8)).max(1);
+    names.insert(gold, "shiny gold".to_string());
+
+    lines(names.iter().enumerate().map(|(i, name)| {
+        let later = names.len() - i - 1;
+        // The shiny gold bag and the one before it always hold something, so neither part is 0.
+        let required = i == gold || i + 1 == gold;
+        let contents: Vec<String> = if later == 0 || (!required && rng.chance(1, 5)) {
+            vec![]
+        } else {
+            let mut picks: Vec<usize> = (0..rng.between(1, later.min(4)))
+                .map(|_| i + 1 + rng.below(later.min(12)))
+                .collect();
+            if i + 1 == gold {
+                picks.push(gold);
+            }
+            picks.sort_unstable();
+            picks.dedup();
+            picks
+                .iter()
+                .map(|&j| {
+                    let count = rng.between(1, 4);
+                    let bags = if count == 1 { "bag" } else { "bags" };
+                    format!("{} {} {}", count, names[j], bags)
+                })
+                .collect()
+        };
+        if contents.is_empty() {
+            format!("{} bags contain no other bags.", name)
+        } else {
+            format!("{} bags contain {}.", name, contents.join(", "))
+        }
+    }))
+}
+
+// The program runs straight through with forward jumps, except for one `nop` that has been
+// corrupted into a `jmp` back to an instruction that already ran. Every `nop` stays in bounds
+// when toggled, so repairing the program never jumps off the end.
+fn program(rng: &mut Rng, size: usize) -> String {
+    let len = size.max(2);
+    let mut code: Vec<(&str, isize)> = (0..len)
+        .map(|i| {
+            let i = i as isize;
+            match rng.below(3) {
+                0 => ("acc", rng.between(0, 100) as isize - 50),
+                1 => (
+                    "jmp",
+                    rng.between(1, (len as isize - i).min(4) as usize) as isize,
+                ),
+                _ => ("nop", rng.between(0, len) as isize - i),
+            }
+        })
+        .collect();
+    // Make sure the first instruction runs as a `nop`, so there is always one to corrupt.
+    code[0] = ("nop", 0);
+
+    let mut executed = Vec::new();
+    let mut ip = 0;
+    while ip < len {
+        executed.push(ip);
+        ip = match code[ip] {
+            ("jmp", offset) => (ip as isize + offset) as usize,
+            _ => ip + 1,
+        };
+    }
+    let nops: Vec<usize> = executed
+        .iter()
+        .copied()
+        .filter(|&i| code[i].0 == "nop")
+        .collect();
+    let corrupt = *rng.choose(&nops);
+    let target = *rng.choose(&executed[..=executed.iter().position(|&i| i == corrupt).unwrap()]);
+    code[corrupt] = ("jmp", target as isize - corrupt as isize);
+
+    lines(
+        code.iter()
+            .map(|(opcode, argument)| format!("{} {:+}", opcode, argument)),
+    )
+}
+
+// Every number after the preamble is the sum of two earlier ones until a planted invalid number,
+// which is the sum of a contiguous run instead. Each sum is at least twice the window's smallest
+// number, so the numbers grow exponentially however they are drawn. Drawing both from the window's
+// smallest few slows that enough that at the cap of 1000 they stayed under 2^54 for each of the
+// first 2000 seeds; drawing from the whole window overflows 64 bits at about 800.
+fn xmas(rng: &mut Rng, size: usize) -> String {
+    const WINDOW: usize = 25;
+    const SMALLEST: usize = 10;
+    let count = size.clamp(WINDOW + 2, 1000);
+    let mut numbers: Vec<usize> = (1..=50).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(WINDOW);
+
+    let invalid_at = rng.between(WINDOW + 1, count - 1);
+    while numbers.len() < count {
+        let window = &numbers[numbers.len() - WINDOW..];
+        let next = if numbers.len() == invalid_at {
+            let start = rng.below(numbers.len() - 2);
+            let run = rng.between(2, 5).min(numbers.len() - start);
+            let sum: usize = numbers[start..start + run].iter().sum();
+            if crate::day9::is_valid(window, sum) || window.contains(&sum) {
+                continue;
+            }
+            sum
+        } else {
+            let mut smallest = window.to_vec();
+            smallest.sort_unstable();
+            smallest.dedup();
+            smallest.truncate(SMALLEST);
+            let (a, b) = (*rng.choose(&smallest), *rng.choose(&smallest));
+            if a == b {
+                continue;
+            }
+            a + b
+        };
+        numbers.push(next);
+    }
+    lines(numbers.iter().map(|n| n.to_string()))
+}
+
+// Gaps of 1 and 3 jolts. Each run of 1-jolt gaps multiplies the number of arrangements, so once
+// that count nears the limit of 64 bits only runs that leave it unchanged are added. Day 10's second
+// part needs an adapter above 3 jolts, which only four or more adapters are sure to include.
+fn adapters(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(4);
+    let mut joltages = Vec::new();
+    let mut joltage = 0;
+    let mut arrangements: u64 = 1;
+    while joltages.len() < size {
+        let run = if arrangements < 1 << 50 {
+            rng.below(5)
+        } else {
+            rng.below(2)
+        };
+        arrangements *= [1, 1, 2, 4, 7][run];
+        for _ in 0..run {
+            joltage += 1;
+            joltages.push(joltage);
+        }
+        joltage += 3;
+        joltages.push(joltage);
+    }
+    joltages.truncate(size);
+    rng.shuffle(&mut joltages);
+    lines(joltages.iter().map(|n| n.to_string()))
+}
+
+// Seat layouts often never settle: the rules can flip some seats back and forth forever. Some of
+// the seats caught flipping under either part's rules are turned into floor until both reach a
+// fixpoint.
+fn seat_layout(rng: &mut Rng, size: usize) -> String {
+    let mut layout = lines((0..size).map(|_| {
+        (0..90)
+            .map(|_| if rng.chance(1, 7) { '.' } else { 'L' })
+            .collect()
+    }));
+    while let Some(flipping) = flipping_seats(&layout, FloorPlan::step)
+        .or_else(|| flipping_seats(&layout, FloorPlan::step2))
+    {
+        layout = layout
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if flipping.contains(&i) && rng.chance(1, 4) {
+                    '.'
+                } else {
+                    c
+                }
+            })
+            .collect();
+    }
+    layout
+}
+
+// Steps the layout until it stops changing, or returns the positions in the text of the seats that
+// change within the cycle of states it falls into. Every state is remembered, so cycles of any
+// length are caught.
+fn flipping_seats(
+    layout: &str,
+    step: fn(&FloorPlan) -> (FloorPlan, bool),
+) -> Option<HashSet<usize>> {
+    let mut floorplan = crate::day11::parse_board(layout.lines()).ok()?;
+    let mut states = vec![floorplan.to_string()];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    seen.insert(states[0].clone(), 0);
+    loop {
+        let (next, changed) = step(&floorplan);
+        if !changed {
+            return None;
+        }
+        let text = next.to_string();
+        if let Some(&start) = seen.get(&text) {
+            let cycle = &states[start..];
+            return Some(
+                cycle
+                    .iter()
+                    .flat_map(|state| {
+                        state
+                            .chars()
+                            .zip(cycle[0].chars())
+                            .enumerate()
+                            .filter(|(_, (a, b))| a != b)
+                            .map(|(i, _)| i)
+                    })
+                    .collect(),
+            );
+        }
+        seen.insert(text.clone(), states.len());
+        states.push(text);
+        floorplan = next;
+    }
+}
+
+fn navigation(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        let action = *rng.choose(&['N', 'S', 'E', 'W', 'L', 'R', 'F', 'F']);
+        let amount = match action {
+            'L' | 'R' => 90 * rng.between(1, 3),
+            _ => rng.between(1, 100),
+        };
+        format!("{}{}", action, amount)
+    }))
+}
+
+const PRIMES: &[usize] = &[
+    13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107,
+    109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193, 197, 199, 211,
+    223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307, 311, 313, 317,
+    331, 337, 347, 349, 353, 359, 367, 373, 379, 383, 389, 397, 401, 409, 419, 421, 431, 433, 439,
+    443, 449, 457, 461, 463, 467, 479, 487, 491, 499, 503, 509, 521, 523, 541, 547, 557, 563, 569,
+    571, 577, 587, 593, 599, 601, 607, 613, 617, 619, 631, 641, 643, 647, 653, 659, 661, 673, 677,
+    683, 691, 701, 709, 719, 727, 733, 739, 743, 751, 757, 761, 769, 773, 787, 797, 809, 811, 821,
+    823, 827, 829, 839, 853, 857, 859, 863, 877, 881, 883, 887, 907, 911, 919, 929, 937, 941, 947,
+    953, 967, 971, 977, 983, 991, 997,
+];
+
+// Bus IDs are distinct primes, as the alignment search needs, and stop being added once their
+// product would no longer fit comfortably in 64 bits.
+fn bus_schedule(rng: &mut Rng, size: usize) -> String {
+    let mut primes = PRIMES.to_vec();
+    rng.shuffle(&mut primes);
+    let mut primes = primes.into_iter();
+    let mut period: u128 = 1;
+    let slots: Vec<String> = (0..size.max(1))
+        .map(|i| {
+            if i == 0 || rng.chance(1, 4) {
+                if let Some(id) = primes.next() {
+                    if period * id as u128 <= 1 << 56 {
+                        period *= id as u128;
+                        return id.to_string();
+                    }
+                }
+            }
+            "x".to_string()
+        })
+        .collect();
+    // No bus leaves right at the start, so the first part never multiplies by a zero wait.
+    let ids: Vec<usize> = slots.iter().filter_map(|slot| slot.parse().ok()).collect();
+    let start = loop {
+        let start = rng.between(100_000, 10_000_000);
+        if ids.iter().all(|&id| !start.is_multiple_of(id)) {
+            break start;
+        }
+    };
+    format!("{}\n{}\n", start, slots.join(","))
+}
+
+// Masks have at most nine floating bits, so decoding an address never writes more than 512
+// locations.
+fn mask_program(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    let mut writes = 0;
+    while writes < size {
+        let floating = rng.below(10);
+        let mut mask: Vec<char> = (0..36)
+            .map(|i| {
+                if i < floating {
+                    'X'
+                } else if rng.chance(1, 2) {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect();
+        rng.shuffle(&mut mask);
+        writeln!(output, "mask = {}", mask.iter().collect::<String>()).unwrap();
+        for _ in 0..rng.between(1, 6).min(size - writes) {
+            writeln!(
+                output,
+                "mem[{}] = {}",
+                rng.below(1 << 16),
+                rng.below(1 << 30)
+            )
+            .unwrap();
+            writes += 1;
+        }
+    }
+    output
+}
+
+fn starting_numbers(rng: &mut Rng, size: usize) -> String {
+    let mut numbers: Vec<usize> = (0..20).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(size.min(10));
+    let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    numbers.join(",") + "\n"
+}
+
+const FIELDS: &[&str] = &[
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+// Every field rejects a small gap of values that the others allow. Nearby ticket `r` puts a value
+// from field `r`'s gap in the column of every field after `r`, so field `k` fits exactly the
+// columns of fields `0..=k` and the fields can be assigned one at a time. About one in eight of
+// the remaining tickets also has a value that no field allows.
+fn tickets(rng: &mut Rng, size: usize) -> String {
+    let fields = size.clamp(1, FIELDS.len());
+    let tickets = size.max(fields);
+    let gap = |k: usize| 50 + 40 * k..=59 + 40 * k;
+    let safe = |rng: &mut Rng| loop {
+        let n = rng.between(41, 949);
+        if (0..fields).all(|k| !gap(k).contains(&n)) {
+            break n;
+        }
+    };
+
+    let mut output = String::new();
+    for (k, name) in FIELDS[..fields].iter().enumerate() {
+        writeln!(
+            output,
+            "{}: {}-{} or {}-{}",
+            name,
+            rng.between(1, 40),
+            gap(k).start() - 1,
+            gap(k).end() + 1,
+            rng.between(950, 974)
+        )
+        .unwrap();
+    }
+
+    let mut columns: Vec<usize> = (0..fields).collect();
+    rng.shuffle(&mut columns);
+    let ticket = |values: Vec<usize>| {
+        let mut row = vec![0; fields];
+        for (k, value) in values.into_iter().enumerate() {
+            row[columns[k]] = value;
+        }
+        let row: Vec<String> = row.iter().map(|n| n.to_string()).collect();
+        row.join(",")
+    };
+
+    let mine = ticket((0..fields).map(|_| safe(rng)).collect());
+    write!(output, "\nyour ticket:\n{}\n\nnearby tickets:\n", mine).unwrap();
+    for r in 0..tickets {
+        let mut values: Vec<usize> = (0..fields)
+            .map(|k| {
+                if k > r {
+                    rng.between(*gap(r).start(), *gap(r).end())
+                } else {
+                    safe(rng)
+                }
+            })
+            .collect();
+        // The first tickets carry the gaps, so only later ones are spoiled.
+        if r + 1 >= fields && rng.chance(1, 8) {
+            values[rng.below(fields)] = rng.between(975, 999);
+        }
+        writeln!(output, "{}", ticket(values)).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner::{run_day, Failure};
+    use crate::solver::DAYS;
+
+    #[test]
+    fn deterministic() {
+        for day in 1..=16 {
+            assert_eq!(generate(day, 42, 50), generate(day, 42, 50));
+        }
+        assert_ne!(generate(1, 1, 50), generate(1, 2, 50));
+        assert_eq!(generate(26, 1, 50), None);
+    }
+
+    #[test]
+    fn inputs_parse_and_solve() {
+        for day in DAYS {
+            // Day 15's second part plays thirty million turns whatever the input.
+            if day.number == 15 {
+                continue;
+            }
+            for seed in 0..3 {
+                let input = generate(day.number, seed, 60).unwrap();
+                let report = run_day(day, &mut input.as_bytes());
+                // Day 16's second part fails on every input, real or generated: it keeps tickets
+                // with any valid value rather than only those whose values are all valid. Once it
+                // is fixed, this stops passing and should go.
+                if day.number == 16 {
+                    assert!(report.part1.is_some(), "day16 seed {}", seed);
+                    assert_eq!(
+                        report.failure,
+                        Some(Failure::Panic),
+                        "day16 part 2 no longer fails on seed {}",
+                        seed
+                    );
+                    continue;
+                }
+                assert!(
+                    report.succeeded(),
+                    "{} seed {}: {:?}",
+                    day.name(),
+                    seed,
+                    report.error
+                );
+            }
+        }
+    }
+
+    // Moves every seat one place to the right, wrapping around: a cycle as long as the row.
+    fn shift_seats(floorplan: &FloorPlan) -> (FloorPlan, bool) {
+        let text = floorplan.to_string();
+        let shifted = text.lines().map(|line| {
+            let (rest, last) = line.split_at(line.len() - 1);
+            format!("{}{}", last, rest)
+        });
+        (crate::day11::parse_board(shifted).unwrap(), true)
+    }
+
+    #[test]
+    fn smallest_inputs_solve() {
+        for day in DAYS {
+            // Day 15 is slow whatever the size, and day 16's second part always fails.
+            if day.number == 15 || day.number == 16 {
+                continue;
+            }
+            for size in 1..=4 {
+                for seed in 0..10 {
+                    let input = generate(day.number, seed, size).unwrap();
+                    let report = run_day(day, &mut input.as_bytes());
+                    assert!(
+                        report.succeeded(),
+                        "{} size {} seed {}: {:?}",
+                        day.name(),
+                        size,
+                        seed,
+                        report.error
+                    );
+                    if day.number == 7 {
+                        assert!(report.part1 != Some(0u64.into()), "seed {}", seed);
+                        assert!(report.part2 != Some(0u64.into()), "seed {}", seed);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_cycles_are_caught() {
+        let flipping = flipping_seats("L#..\n....\n", shift_seats).unwrap();
+        let mut flipping: Vec<usize> = flipping.into_iter().collect();
+        flipping.sort_unstable();
+        assert_eq!(flipping, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn xmas_at_the_cap() {
+        let day = DAYS.iter().find(|day| day.number == 9).unwrap();
+        for seed in 0..5 {
+            let input = generate(9, seed, 1000).unwrap();
+            assert_eq!(input.lines().count(), 1000);
+            let report = run_day(day, &mut input.as_bytes());
+            assert!(report.succeeded(), "seed {}: {:?}", seed, report.error);
+        }
+    }
+
+    #[test]
+    fn planted_expenses() {
+        let input = generate(1, 9, 200).unwrap();
+        let expenses: Vec<usize> = input.lines().map(|n| n.parse().unwrap()).collect();
+        assert_eq!(expenses.len(), 200);
+        let pairs = combinations(&expenses, 2)
+            .filter(|&sum| sum == 2020)
+            .count();
+        assert_eq!(pairs, 1);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::error::{parse_lines, LineError, ParseError};
+use std::borrow::Borrow;
+use std::fmt;
+
+/// A row and column in a grid. Either may lie past an edge, in which case the grid's `Boundary`
+/// decides what is found there.
+pub type Position = (isize, isize);
+
+/// The steps from a cell to each of its eight neighbors.
+pub const DIRECTIONS: [Position; 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// What lies beyond the edges of a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary {
+    /// Every cell outside the grid holds the default value.
+    Default,
+    /// The columns repeat forever to the left and right. There is nothing above or below.
+    WrapColumns,
+    /// The grid repeats forever in every direction.
+    Wrap,
+}
+
+/// A rectangular grid of cells, stored row by row in one buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    boundary: Boundary,
+    outside: T,
+}
+
+impl<T: Default> Grid<T> {
+    /// Builds a grid from its cells in row order. `cells` must hold `width * height` of them.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "grid has the wrong size");
+        Grid {
+            width,
+            height,
+            cells,
+            boundary: Boundary::Default,
+            outside: T::default(),
+        }
+    }
+
+    /// Parses a character map, one row per line, turning each character into a cell with `cell`.
+    ///
+    /// Characters that `cell` rejects are reported as errors, as are rows that are empty or differ
+    /// in width from the first.
+    pub fn parse<Lines, F>(lines: Lines, mut cell: F) -> Result<Grid<T>, ParseError>
+    where
+        Lines: Iterator,
+        Lines::Item: Borrow<str>,
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let rows = parse_lines(lines, |line| {
+            let row = line
+                .char_indices()
+                .map(|(i, c)| {
+                    cell(c).ok_or_else(|| {
+                        LineError::character(line, i, format!("unsupported character `{}`", c))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if row.is_empty() || *width.get_or_insert(row.len()) != row.len() {
+                return Err(LineError::whole(
+                    line,
+                    "every row must have the same, non-zero width",
+                ));
+            }
+            Ok(row)
+        })?;
+
+        let height = rows.len();
+        Ok(Grid::new(
+            width.unwrap_or(0),
+            height,
+            rows.into_iter().flatten().collect(),
+        ))
+    }
+
+    /// Builds a grid of the same shape and boundary, computing each cell with `f`.
+    pub fn map<U: Default, F: FnMut(Position, &T) -> U>(&self, mut f: F) -> Grid<U> {
+        let cells = self
+            .positions()
+            .zip(&self.cells)
+            .map(|(position, cell)| f(position, cell))
+            .collect();
+        Grid::new(self.width, self.height, cells).with_boundary(self.boundary)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn with_boundary(mut self, boundary: Boundary) -> Grid<T> {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `position` is inside the grid's edges, regardless of the boundary.
+    pub fn contains(&self, (i, j): Position) -> bool {
+        i >= 0 && j >= 0 && (i as usize) < self.height && (j as usize) < self.width
+    }
+
+    /// The cell at `position`, or `None` if the boundary puts nothing there.
+    pub fn get(&self, position: Position) -> Option<&T> {
+        if let Some(index) = self.index_of(position) {
+            return Some(&self.cells[index]);
+        }
+        match self.boundary {
+            Boundary::Default => Some(&self.outside),
+            Boundary::WrapColumns | Boundary::Wrap => None,
+        }
+    }
+
+    /// Every position inside the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        (0..height).flat_map(move |i| (0..width).map(move |j| (i, j)))
+    }
+
+    /// Every cell inside the grid, row by row.
+    pub fn cells(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// The cells around `position`, skipping any the boundary leaves empty.
+    pub fn neighbors(&self, (i, j): Position) -> impl Iterator<Item = &T> + '_ {
+        DIRECTIONS
+            .iter()
+            .filter_map(move |(di, dj)| self.get((i + di, j + dj)))
+    }
+
+    /// The cells seen looking from `position` in `direction`, up to the grid's edge.
+    ///
+    /// The edge stops the ray whatever the boundary, so it never repeats cells.
+    pub fn ray(&self, (i, j): Position, (di, dj): Position) -> impl Iterator<Item = &T> + '_ {
+        (1..)
+            .map(move |n| (i + n * di, j + n * dj))
+            .take_while(move |&position| self.contains(position) && (di, dj) != (0, 0))
+            .map(move |position| &self[position])
+    }
+
+    /// Finds where `position` lands in `cells` once the boundary has been applied.
+    fn index_of(&self, (i, j): Position) -> Option<usize> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (i, j) = match self.boundary {
+            Boundary::Default => (i, j),
+            Boundary::WrapColumns => (i, j.rem_euclid(self.width as isize)),
+            Boundary::Wrap => (
+                i.rem_euclid(self.height as isize),
+                j.rem_euclid(self.width as isize),
+            ),
+        };
+        if self.contains((i, j)) {
+            Some(i as usize * self.width + j as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> std::ops::Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        match self.get(position) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside the grid", position),
+        }
+    }
+}
+
+/// Prints the grid back as a character map, which `Grid::parse` can read again.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Cell(char);
+
+    impl fmt::Display for Cell {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    const MAP: &str = "abc\ndef\n";
+
+    fn letters(map: &str) -> Result<Grid<Cell>, ParseError> {
+        Grid::parse(map.lines(), |c| {
+            Some(Cell(c)).filter(|_| c.is_ascii_lowercase())
+        })
+    }
+
+    #[test]
+    fn parse_and_print() {
+        let grid = letters(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], Cell('d'));
+        assert_eq!(grid.to_string(), MAP);
+        assert_eq!(letters(&grid.to_string()).unwrap(), grid);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = letters("abc\ndEf").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.message, "unsupported character `E`");
+        let error = letters("abc\nde").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn boundaries() {
+        let grid = letters(MAP).unwrap();
+        assert_eq!(grid.get((-1, 0)), Some(&Cell::default()));
+        assert_eq!(grid.get((2, 1)), Some(&Cell::default()));
+
+        let grid = grid.with_boundary(Boundary::WrapColumns);
+        assert_eq!(grid.get((0, 4)), Some(&Cell('b')));
+        assert_eq!(grid.get((1, -1)), Some(&Cell('f')));
+        assert_eq!(grid.get((2, 0)), None);
+
+        let grid = grid.with_boundary(Boundary::Wrap);
+        assert_eq!(grid.get((2, 0)), Some(&Cell('a')));
+        assert_eq!(grid.get((-3, -5)), Some(&Cell('e')));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = letters(MAP).unwrap();
+        let around = |position| -> String { grid.neighbors(position).map(|c| c.0).collect() };
+        assert_eq!(around((0, 0)), "\0\0\0\0b\0de");
+        assert_eq!(around((1, 1)), "abcdf\0\0\0");
+
+        let grid = grid.with_boundary(Boundary::WrapColumns);
+        let around = |position| -> String { grid.neighbors(position).map(|c| c.0).collect() };
+        assert_eq!(around((0, 0)), "cbfde");
+    }
+
+    #[test]
+    fn rays() {
+        let grid = letters("abcd\nefgh\nijkl\n").unwrap();
+        let ray = |direction| -> String { grid.ray((0, 0), direction).map(|c| c.0).collect() };
+        assert_eq!(ray((0, 1)), "bcd");
+        assert_eq!(ray((1, 1)), "fk");
+        assert_eq!(ray((-1, 0)), "");
+    }
+
+    #[test]
+    fn map_cells() {
+        let grid = letters(MAP).unwrap().with_boundary(Boundary::Wrap);
+        let mapped = grid.map(|(i, j), cell| Cell(if i == j { '*' } else { cell.0 }));
+        assert_eq!(mapped.to_string(), "*bc\nd*f\n");
+        assert_eq!(mapped.get((3, 4)), Some(&Cell('*')));
+    }
+}
@@ -0,0 +1,590 @@
+//! Just enough HTTP/1.1 to serve and make simple requests, one per connection, over plain TCP.
+
+use crate::json::Json;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv6Addr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// The most header bytes, request line included, that `Request::read` accepts.
+pub const MAX_HEADER: usize = 8 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// The path, without any query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// A request that could not be read, with the status to answer it with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl HttpError {
+    pub fn new<S: Into<String>>(status: u16, message: S) -> HttpError {
+        HttpError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn to_response(&self) -> Response {
+        Response::json(
+            self.status,
+            &Json::object(vec![("error", Json::from(self.message.as_str()))]),
+        )
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.status,
+            reason(self.status),
+            self.message
+        )
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(e: io::Error) -> HttpError {
+        match e.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+                HttpError::new(408, "timed out reading the request")
+            }
+            _ => HttpError::new(400, e.to_string()),
+        }
+    }
+}
+
+impl Request {
+    /// Reads a request, refusing bodies over `max_body` bytes.
+    pub fn read<R: BufRead>(reader: &mut R, max_body: usize) -> Result<Request, HttpError> {
+        let mut header = reader.take(MAX_HEADER as u64);
+        let request_line =
+            read_line(&mut header)?.ok_or_else(|| HttpError::new(400, "empty request"))?;
+        let mut parts = request_line.split(' ');
+        let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version)) if parts.next().is_none() => {
+                (method, target, version)
+            }
+            _ => return Err(HttpError::new(400, "malformed request line")),
+        };
+        if !version.starts_with("HTTP/1.") {
+            return Err(HttpError::new(
+                505,
+                format!("unsupported version {}", version),
+            ));
+        }
+        let path = target.split('?').next().unwrap_or_default().to_string();
+        let method = method.to_string();
+        let headers = read_headers(&mut header)?;
+        let reader = header.into_inner();
+
+        let mut request = Request {
+            method,
+            path,
+            headers,
+            body: Vec::new(),
+        };
+        if request.header("transfer-encoding").is_some() {
+            return Err(HttpError::new(411, "send the body with a Content-Length"));
+        }
+        let length = match request.header("content-length") {
+            None => 0,
+            Some(length) => length
+                .parse::<usize>()
+                .map_err(|_| HttpError::new(400, "bad Content-Length"))?,
+        };
+        if length > max_body {
+            return Err(HttpError::new(
+                413,
+                format!("the body is limited to {} bytes", max_body),
+            ));
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+        Ok(request)
+    }
+
+    /// The value of the named header, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// A response to send, or one that was received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new<B: Into<Vec<u8>>>(status: u16, content_type: &str, body: B) -> Response {
+        Response {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn json(status: u16, json: &Json) -> Response {
+        Response::new(status, "application/json", json.to_string() + "\n")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// The value of the named header, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Writes the response, closing the connection after it.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            reason(self.status)
+        )?;
+        for (name, value) in &self.headers {
+            write!(writer, "{}: {}\r\n", name, value)?;
+        }
+        write!(
+            writer,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        )?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+}
+
+impl Response {
+    /// Reads a response, with a body sized by `Content-Length`, sent in chunks, or running to the
+    /// end of the connection.
+    pub fn read<R: BufRead>(reader: &mut R) -> io::Result<Response> {
+        let mut header = reader.take(MAX_HEADER as u64);
+        let status_line = read_line(&mut header)
+            .map_err(invalid)?
+            .ok_or_else(|| invalid(HttpError::new(0, "empty response")))?;
+        let status = match status_line.split(' ').collect::<Vec<_>>().as_slice() {
+            [version, status, ..] if version.starts_with("HTTP/1.") => status.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            invalid(HttpError::new(
+                0,
+                format!("bad status line {:?}", status_line),
+            ))
+        })?;
+        let headers = read_headers(&mut header).map_err(invalid)?;
+        let reader = header.into_inner();
+
+        let mut response = Response {
+            status,
+            headers,
+            body: Vec::new(),
+        };
+        if response
+            .header("transfer-encoding")
+            .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+        {
+            response.body = read_chunks(reader)?;
+        } else if let Some(length) = response.header("content-length") {
+            let length = length
+                .parse()
+                .map_err(|_| invalid(HttpError::new(0, "bad Content-Length")))?;
+            response.body = vec![0; length];
+            reader.read_exact(&mut response.body)?;
+        } else {
+            reader.read_to_end(&mut response.body)?;
+        }
+        Ok(response)
+    }
+
+    /// The body as text, with anything that is not UTF-8 replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+fn read_chunks<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)
+            .map_err(invalid)?
+            .ok_or_else(|| invalid(HttpError::new(0, "missing chunk")))?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| invalid(HttpError::new(0, format!("bad chunk size {:?}", line))))?;
+        if size == 0 {
+            // Skip any trailer.
+            while read_line(reader)
+                .map_err(invalid)?
+                .is_some_and(|line| !line.is_empty())
+            {}
+            return Ok(body);
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        read_line(reader).map_err(invalid)?;
+    }
+}
+
+fn invalid(e: HttpError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.message)
+}
+
+/// An `http://` URL, split into where to connect and the path to ask for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Url {
+    /// The host, without the brackets an IPv6 address is written in.
+    pub host: String,
+    pub port: u16,
+    /// The path, without a trailing slash, so `join` can add to it.
+    pub path: String,
+}
+
+impl Url {
+    pub fn parse(text: &str) -> Result<Url, String> {
+        let rest = text
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("only plain http:// URLs are supported, not {}", text))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.strip_prefix('[') {
+            // An IPv6 address, whose colons are not the port's.
+            Some(rest) => {
+                let (host, rest) = rest
+                    .split_once(']')
+                    .ok_or_else(|| format!("unclosed [ in {}", text))?;
+                if host.parse::<Ipv6Addr>().is_err() {
+                    return Err(format!("bad IPv6 address in {}", text));
+                }
+                match rest {
+                    "" => (host, None),
+                    _ => match rest.strip_prefix(':') {
+                        Some(port) => (host, Some(port)),
+                        None => return Err(format!("bad port in {}", text)),
+                    },
+                }
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        if host.is_empty() {
+            return Err(format!("no host in {}", text));
+        }
+        if host.contains(':') && !authority.starts_with('[') {
+            return Err(format!("IPv6 addresses go in brackets, in {}", text));
+        }
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| format!("bad port in {}", text))?,
+            None => 80,
+        };
+        Ok(Url {
+            host: host.to_string(),
+            port,
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Whether the host is this machine, so that requests to it never cross the network.
+    pub fn is_loopback(&self) -> bool {
+        self.host.eq_ignore_ascii_case("localhost")
+            || self.host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+    }
+
+    /// The host as written in a URL or a `Host` header, with an IPv6 address in brackets.
+    pub fn bracketed_host(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        }
+    }
+
+    /// This URL's path followed by `path`, which should start with `/`.
+    pub fn join(&self, path: &str) -> String {
+        format!("{}{}", self.path, path)
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "http://{}:{}{}",
+            self.bracketed_host(),
+            self.port,
+            self.path
+        )
+    }
+}
+
+/// Sends a request for `path` on `url`'s server and reads the response. `timeout` applies to
+/// connecting and to each read and write.
+pub fn send(
+    url: &Url,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    timeout: Duration,
+) -> io::Result<Response> {
+    exchange(connect(url, timeout)?, url, method, path, headers, body)
+}
+
+/// Connects to `url`'s server, with `timeout` applying to connecting and to each later read and
+/// write. Nothing has been sent if this fails.
+pub fn connect(url: &Url, timeout: Duration) -> io::Result<TcpStream> {
+    let address = (url.host.as_str(), url.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} not found", url.host))
+        })?;
+    let stream = TcpStream::connect_timeout(&address, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(stream)
+}
+
+/// Sends a request for `path` over `stream`, a connection to `url`'s server, and reads the
+/// response.
+pub fn exchange(
+    stream: TcpStream,
+    url: &Url,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> io::Result<Response> {
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        url.join(path),
+        url.bracketed_host(),
+        body.len()
+    );
+    for (name, value) in headers {
+        request += &format!("{}: {}\r\n", name, value);
+    }
+    request += "\r\n";
+    let mut writer = &stream;
+    writer.write_all(request.as_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()?;
+    Response::read(&mut BufReader::new(&stream))
+}
+
+/// Passes on a 200 response to a request for `path` on `url`, and turns anything else into an
+/// error quoting the first line of the body.
+pub fn expect_ok(url: &Url, path: &str, response: Response) -> io::Result<Response> {
+    if response.status == 200 {
+        return Ok(response);
+    }
+    let text = response.text();
+    Err(io::Error::other(format!(
+        "{}{} answered {} {}: {}",
+        url,
+        path,
+        response.status,
+        reason(response.status),
+        text.lines().next().unwrap_or_default()
+    )))
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+// Reads a line without its line ending, or `None` at the end of the input.
+fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, HttpError> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        return Err(HttpError::new(431, "the request header is too long"));
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| HttpError::new(400, "the request header is not UTF-8"))
+}
+
+fn read_headers<R: BufRead>(reader: &mut R) -> Result<Vec<(String, String)>, HttpError> {
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or_else(|| HttpError::new(400, "incomplete header"))?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        let colon = line
+            .find(':')
+            .ok_or_else(|| HttpError::new(400, format!("malformed header {:?}", line)))?;
+        headers.push((
+            line[..colon].trim().to_string(),
+            line[colon + 1..].trim().to_string(),
+        ));
+    }
+}
+
+/// The standard reason phrase for the statuses used here.
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        301 => "Moved Permanently",
+        302 => "Found",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        505 => "HTTP Version Not Supported",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::stand_in;
+
+    fn read(text: &str, max_body: usize) -> Result<Request, HttpError> {
+        Request::read(&mut text.as_bytes(), max_body)
+    }
+
+    #[test]
+    fn read_request() {
+        let request = read(
+            "POST /day1?x=1 HTTP/1.1\r\nHost: here\r\ncontent-length: 5\r\n\r\n1\n2\n3",
+            100,
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/day1");
+        assert_eq!(request.header("Content-Length"), Some("5"));
+        assert_eq!(request.body, b"1\n2\n3");
+
+        let request = read("GET / HTTP/1.0\n\n", 0).unwrap();
+        assert_eq!((request.path.as_str(), request.body.len()), ("/", 0));
+    }
+
+    #[test]
+    fn bad_requests() {
+        let status = |text: &str| read(text, 4).unwrap_err().status;
+        assert_eq!(status(""), 400);
+        assert_eq!(status("GET /\r\n\r\n"), 400);
+        assert_eq!(status("GET / SPDY/3\r\n\r\n"), 505);
+        assert_eq!(status("GET / HTTP/1.1\r\nHost here\r\n\r\n"), 400);
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n12345"),
+            413
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\n12"),
+            400
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"),
+            411
+        );
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEADER));
+        assert_eq!(status(&long), 431);
+    }
+
+    #[test]
+    fn write_response() {
+        let mut out = Vec::new();
+        Response::new(404, "text/plain", "nope")
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 4\r\nConnection: close\r\n\r\nnope"
+        );
+    }
+
+    #[test]
+    fn parse_url() {
+        let url = Url::parse("http://localhost:8000/aoc/2020/").unwrap();
+        assert_eq!((url.host.as_str(), url.port), ("localhost", 8000));
+        assert_eq!(url.join("/day/1/input"), "/aoc/2020/day/1/input");
+        assert_eq!(Url::parse("http://example.com").unwrap().port, 80);
+        assert!(Url::parse("https://example.com").is_err());
+        assert!(Url::parse("http://:80/").is_err());
+
+        let url = Url::parse("http://[::1]/").unwrap();
+        assert_eq!((url.host.as_str(), url.port), ("::1", 80));
+        assert_eq!(url.to_string(), "http://[::1]:80");
+        let url = Url::parse("http://[::1]:8000/aoc").unwrap();
+        assert_eq!((url.host.as_str(), url.port), ("::1", 8000));
+        assert!(url.is_loopback());
+        assert!(Url::parse("http://[::1/").is_err());
+        assert!(Url::parse("http://[::1]x/").is_err());
+        assert!(Url::parse("http://[example.com]/").is_err());
+        assert!(Url::parse("http://::1:80/").is_err());
+    }
+
+    #[test]
+    fn read_response() {
+        let read = |text: &str| Response::read(&mut text.as_bytes()).unwrap();
+        let response = read("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nabcdef");
+        assert_eq!((response.status, response.text().as_str()), (200, "abc"));
+        let response = read("HTTP/1.1 404 Not Found\r\n\r\ngone\n");
+        assert_eq!((response.status, response.text().as_str()), (404, "gone\n"));
+        let response =
+            read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;x=y\r\nde\r\n0\r\n\r\n");
+        assert_eq!(response.text(), "abcde");
+        assert!(Response::read(&mut "HTTP/1.1 abc\r\n\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let (url, requests) = stand_in(vec![Response::new(200, "text/plain", "pong")]);
+        let response = send(
+            &url,
+            "POST",
+            "/ping",
+            &[("Cookie", "session=abc")],
+            b"ping",
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(response.text(), "pong");
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/base/ping");
+        assert_eq!(request.header("cookie"), Some("session=abc"));
+        assert_eq!(request.body, b"ping");
+    }
+}
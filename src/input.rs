@@ -0,0 +1,112 @@
+use crate::solver::Day;
+use std::io::{BufRead, IsTerminal, Stdin};
+use std::path::PathBuf;
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Source {
+    /// Picks the input for `day`.
+    ///
+    /// An explicit path wins, with `-` meaning stdin. Otherwise stdin is used if it is a pipe or
+    /// a file with something in it, and the checked-in `data/dayN.txt` if not. So a terminal, or
+    /// the `/dev/null` that cron and CI jobs are often run with, falls back to the data file.
+    pub fn resolve(day: &Day, path: Option<&str>) -> Source {
+        resolve_with(day, path, stdin_has_input)
+    }
+
+    pub fn open(&self) -> std::io::Result<Box<dyn BufRead>> {
+        match self {
+            Source::Stdin => Ok(Box::new(std::io::BufReader::new(std::io::stdin()))),
+            Source::File(path) => match std::fs::File::open(path) {
+                Ok(file) => Ok(Box::new(std::io::BufReader::new(file))),
+                Err(e) => Err(std::io::Error::new(
+                    e.kind(),
+                    format!("could not open {}: {}", path.display(), e),
+                )),
+            },
+        }
+    }
+
+    /// Reads the whole input into memory.
+    pub fn read(&self) -> std::io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.open()?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Stdin => write!(f, "<stdin>"),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+fn resolve_with(day: &Day, path: Option<&str>, stdin_has_input: impl FnOnce() -> bool) -> Source {
+    match path {
+        Some("-") => Source::Stdin,
+        Some(path) => Source::File(path.into()),
+        None if stdin_has_input() => Source::Stdin,
+        None => Source::File(day.data_path()),
+    }
+}
+
+// Whether stdin is a pipe or file that is not empty. This waits for a pipe's first bytes, which
+// stay in stdin's buffer for whoever reads it next.
+fn stdin_has_input() -> bool {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() || !is_pipe_or_file(&stdin) {
+        return false;
+    }
+    matches!(stdin.lock().fill_buf(), Ok(bytes) if !bytes.is_empty())
+}
+
+#[cfg(unix)]
+fn is_pipe_or_file(stdin: &Stdin) -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+    stdin
+        .as_fd()
+        .try_clone_to_owned()
+        .and_then(|fd| std::fs::File::from(fd).metadata())
+        .is_ok_and(|metadata| metadata.file_type().is_fifo() || metadata.is_file())
+}
+
+// Elsewhere, reading is the only way to tell.
+#[cfg(not(unix))]
+fn is_pipe_or_file(_: &Stdin) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+
+    #[test]
+    fn explicit_path() {
+        let day = find_day("day7").unwrap();
+        assert_eq!(
+            resolve_with(day, Some("other.txt"), || true),
+            Source::File("other.txt".into())
+        );
+        assert_eq!(resolve_with(day, Some("-"), || false), Source::Stdin);
+    }
+
+    #[test]
+    fn default_path() {
+        let day = find_day("day7").unwrap();
+        assert_eq!(
+            resolve_with(day, None, || false),
+            Source::File("data/day7.txt".into())
+        );
+        assert_eq!(resolve_with(day, None, || true), Source::Stdin);
+    }
+}
@@ -0,0 +1,215 @@
+//! `isolate`: runs work where it cannot take the caller down with it.
+//!
+//! A thread that overflows its stack aborts the whole process, and a thread that never returns
+//! cannot be stopped. So work that might do either runs in a child process, usually this
+//! executable running a hidden command, which can be killed when its time is up and whose crash is
+//! its own. The child reads its work from stdin and does it with `on_thread`.
+
+use crate::runner::catch;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{self, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The stack work runs with, in a child process or not. It is the usual size of a main thread's
+/// stack, so how deep work can recurse does not depend on which thread it lands on.
+pub const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The exit code of a child that caught a panic. The panic's message is on its stderr.
+pub const PANICKED: i32 = 3;
+
+// The longest wait between checks on a running child. Checks start closer together, since most
+// children are quick.
+const MAX_POLL: Duration = Duration::from_millis(10);
+
+/// How to start a child: a program and the arguments that come before each child's own.
+#[derive(Clone, Debug)]
+pub struct Command {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// How a child ended.
+#[derive(Debug, PartialEq)]
+pub enum Exit {
+    /// It exited successfully, having written this to stdout.
+    Finished(Vec<u8>),
+    /// It caught a panic with this message.
+    Panicked(String),
+    /// It died some other way, such as by overflowing its stack. This says how.
+    Crashed(String),
+    /// It was still running when its time ran out, so it was killed.
+    TimedOut,
+}
+
+impl Command {
+    pub fn new<P: Into<PathBuf>>(program: P, args: &[&str]) -> Command {
+        Command {
+            program: program.into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// This executable, with `args`.
+    pub fn current_exe(args: &[&str]) -> io::Result<Command> {
+        Ok(Command::new(std::env::current_exe()?, args))
+    }
+
+    /// Runs a child with `args` after the command's own and `input` on its stdin, killing it if
+    /// it is still running after `timeout`.
+    pub fn run(&self, args: &[&str], input: &[u8], timeout: Duration) -> io::Result<Exit> {
+        let mut child = process::Command::new(&self.program)
+            .args(&self.args)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Each pipe gets a thread of its own, so a child blocked on a full pipe cannot keep us
+        // waiting on another. A child may exit without reading its input, so writes can fail.
+        let stdin = child.stdin.take().map(|mut stdin| {
+            let input = input.to_vec();
+            thread::spawn(move || drop(stdin.write_all(&input)))
+        });
+        let stdout = child.stdout.take().map(drain);
+        let stderr = child.stderr.take().map(drain);
+
+        let deadline = Instant::now() + timeout;
+        let mut poll = Duration::from_micros(50);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if Instant::now() >= deadline {
+                child.kill()?;
+                child.wait()?;
+                break None;
+            }
+            thread::sleep(poll);
+            poll = (poll * 2).min(MAX_POLL);
+        };
+        if let Some(stdin) = stdin {
+            let _ = stdin.join();
+        }
+        let stdout = stdout.map(collect).unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr.map(collect).unwrap_or_default()).into_owned();
+
+        Ok(match status {
+            None => Exit::TimedOut,
+            Some(status) if status.success() => Exit::Finished(stdout),
+            Some(status) if status.code() == Some(PANICKED) => {
+                Exit::Panicked(stderr.trim_end().to_string())
+            }
+            Some(status) => match stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+                Some(line) => Exit::Crashed(format!("{} ({})", line, status)),
+                None => Exit::Crashed(status.to_string()),
+            },
+        })
+    }
+}
+
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+fn collect(reader: JoinHandle<Vec<u8>>) -> Vec<u8> {
+    reader.join().unwrap_or_default()
+}
+
+/// Starts `f` on a thread of its own with a stack of `STACK_SIZE`.
+///
+/// Its result, or the message it panicked with, is sent to the returned receiver. Waiting on that
+/// with a timeout gives up on the thread, but does not stop it.
+pub fn spawn<T, F>(f: F) -> io::Result<Receiver<Result<T, String>>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("worker".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let _ = sender.send(catch(f));
+        })?;
+    Ok(receiver)
+}
+
+/// Runs `f` to completion on a thread started by `spawn`, returning its result or the message it
+/// panicked with.
+pub fn on_thread<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let receiver = spawn(f).map_err(|e| format!("could not start a worker: {}", e))?;
+    receiver
+        .recv()
+        .unwrap_or_else(|_| Err("the worker exited without a result".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sh(script: &str) -> Command {
+        Command::new("sh", &["-c", script])
+    }
+
+    #[test]
+    fn exits() {
+        let second = Duration::from_secs(5);
+        assert_eq!(
+            sh("cat").run(&[], b"input", second).unwrap(),
+            Exit::Finished(b"input".to_vec())
+        );
+        assert_eq!(
+            sh("echo \"$0\" >&2; exit 3")
+                .run(&["oops"], b"", second)
+                .unwrap(),
+            Exit::Panicked("oops".to_string())
+        );
+        match sh("echo 'overflowed' >&2; kill -ABRT $$").run(&[], b"", second) {
+            Ok(Exit::Crashed(how)) => assert!(how.starts_with("overflowed ("), "{}", how),
+            other => panic!("expected a crash, got {:?}", other),
+        }
+        let started = Instant::now();
+        assert_eq!(
+            sh("exec sleep 10")
+                .run(&[], b"", Duration::from_millis(100))
+                .unwrap(),
+            Exit::TimedOut
+        );
+        assert!(started.elapsed() < second);
+        assert!(Command::new("/nonexistent", &[])
+            .run(&[], b"", second)
+            .is_err());
+    }
+
+    #[test]
+    fn threads() {
+        assert_eq!(on_thread(|| 6 * 7), Ok(42));
+        assert_eq!(
+            on_thread(|| panic!("bang")),
+            Err::<(), _>("bang".to_string())
+        );
+
+        // Deeper than a spawned thread's default stack allows.
+        fn depth(n: usize) -> usize {
+            let padding = [n as u8; 256];
+            if n == 0 {
+                0
+            } else {
+                1 + depth(n - 1) + usize::from(padding[n % 256] == 1)
+            }
+        }
+        assert!(on_thread(|| depth(10_000)).unwrap() >= 10_000);
+    }
+}
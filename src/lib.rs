@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate regex;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+
+pub mod answer;
+pub mod answers;
+pub mod batch;
+pub mod bench;
+pub mod budget;
+pub mod cache;
+pub mod differential;
+pub mod editor;
+pub mod error;
+pub mod explain;
+pub mod fetch;
+pub mod fuzz;
+pub mod generate;
+pub mod grid;
+pub mod hash;
+pub mod http;
+pub mod input;
+pub mod isolate;
+pub mod json;
+pub mod records;
+pub mod repl;
+pub mod rng;
+pub mod runner;
+pub mod scaffold;
+pub mod server;
+pub mod solver;
+pub mod submit;
+pub mod testing;
@@ -1,89 +1,981 @@
-#[macro_use]
-extern crate lazy_static;
-extern crate regex;
-
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod util;
-
+use adventofcode2020::{
+    answer, answers, batch, bench, budget, cache, editor, error, explain, fetch, fuzz, generate,
+    input, isolate, repl, runner, scaffold, server, solver, submit,
+};
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::process::exit;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            usage();
+            exit(1);
+        }
+    };
+
+    match args.positional.first().map(String::as_str) {
+        None => {
+            usage();
+            exit(1);
+        }
+        Some("all") => run_all(&args),
+        Some("bench") => run_bench(&args),
+        Some("batch") => run_batch(&args),
+        Some("verify") => run_verify(&args),
+        Some("new-day") => run_new_day(&args),
+        Some("generate") => run_generate(&args),
+        Some("fuzz") => run_fuzz(&args),
+        Some("fuzz-child") => run_fuzz_child(&args),
+        Some("serve") => run_serve(&args),
+        Some("solve-child") => run_solve_child(&args),
+        Some("fetch") => run_fetch(&args),
+        Some("submit") => run_submit(&args),
+        Some("repl") => run_repl(&args),
+        Some("cache") => run_cache(&args),
+        Some("list") => {
+            for day in solver::DAYS {
+                println!("{}", day.name());
+            }
+        }
+        Some(name) if args.flag("explain") => {
+            // Steps go to stderr so the answers on stdout are unchanged.
+            let day = name.to_string();
+            explain::with_sink(
+                move |step| eprintln!("{}: {}", day, step),
+                || run_one(name, &args),
+            )
+        }
+        Some(name) => run_one(name, &args),
+    }
+}
+
+fn usage() {
+    println!("Usage example: adventofcode2020 day1 < input.txt");
+    println!("               adventofcode2020 day1 --explain");
+    println!("               adventofcode2020 day1 --input data/day1.txt --format json");
+    println!("               adventofcode2020 day1 --timeout SECONDS --max-steps N");
+    println!("               adventofcode2020 day1 --no-cache");
+    println!("               adventofcode2020 all [--format human|json] [--jobs N] [--timeout SECONDS]");
+    println!("               adventofcode2020 bench [dayN...] [--iterations N]");
+    println!("               adventofcode2020 batch dayN DIR [--csv FILE|-] [--timeout SECONDS]");
+    println!("               adventofcode2020 verify [dayN...] [--answers FILE] [--record] [--timeout SECONDS]");
+    println!("               adventofcode2020 generate dayN [--seed S] [--size N]");
+    println!("               adventofcode2020 fuzz [TARGET...] [--iterations N] [--seed S] [--corpus DIR]");
+    println!("               adventofcode2020 serve [--port N] [--max-body BYTES] [--max-in-flight N] [--timeout SECONDS]");
+    println!("               adventofcode2020 fetch dayN --base-url URL [--insecure] [--force] [--session FILE]");
+    println!("               adventofcode2020 submit dayN partK --base-url URL [--insecure] [--answer X] [--input FILE] [--history FILE] [--cooldown SECONDS]");
+    println!("               adventofcode2020 repl dayN [--input FILE] [--timeout SECONDS]");
+    println!("               adventofcode2020 cache clear");
+    println!("               adventofcode2020 new-day N");
+    println!("               adventofcode2020 list");
+}
+
+/// Options that are switched on by their presence and take no value.
+const FLAGS: &[&str] = &["record", "explain", "force", "no-cache", "insecure"];
+
+/// The command line, split into positional arguments, `--name value` options and `--flag`s.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut flags = HashSet::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => {
+                    flags.insert(name.to_string());
+                }
+                Some(name) => {
+                    let (name, value) = match name.find('=') {
+                        Some(i) => (name[..i].to_string(), name[i + 1..].to_string()),
+                        None => match args.next() {
+                            Some(value) => (name.to_string(), value),
+                            None => return Err(format!("Missing value for --{}", name)),
+                        },
+                    };
+                    options.insert(name, value);
+                }
+                None => positional.push(arg),
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+            flags,
+        })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+/// How answers are printed.
+#[derive(PartialEq)]
+enum Format {
+    Human,
+    /// One JSON object per day, one per line.
+    Json,
+}
+
+impl Format {
+    fn from_args(args: &Args) -> Format {
+        match args.option("format") {
+            None | Some("human") => Format::Human,
+            Some("json") => Format::Json,
+            Some(other) => {
+                eprintln!("Unknown format: {}", other);
+                exit(1);
+            }
+        }
+    }
+}
+
+fn run_all(args: &Args) {
+    let format = Format::from_args(args);
+    let jobs = match args.option("jobs") {
+        None => std::thread::available_parallelism().map_or(1, usize::from),
+        Some(jobs) => match jobs.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                eprintln!("--jobs must be a positive number");
+                exit(1);
+            }
+        },
+    };
+    budget::silent_panic_hook();
+    let mut cache = load_cache(args);
+    let reports = match &mut cache {
+        Some(cache) => {
+            let days: Vec<&solver::Day> = solver::DAYS.iter().collect();
+            cache::run_days(cache, &days, jobs, budget_from_args(args))
+        }
+        None => runner::run_all(jobs, budget_from_args(args)),
+    };
+    save_cache(cache.as_ref());
+    match format {
+        Format::Human => print_table(reports.as_slice()),
+        Format::Json => {
+            for report in &reports {
+                println!("{}", report.to_json());
+            }
+        }
+    }
+    if !reports.iter().all(runner::Report::succeeded) {
+        exit(1);
+    }
+}
+
+// Looks up the named days, or returns every day if none are named.
+fn selected_days(names: &[String]) -> Vec<&'static solver::Day> {
+    if names.is_empty() {
+        return solver::DAYS.iter().collect();
+    }
+    names
+        .iter()
+        .map(|name| match solver::find_day(name) {
+            Some(day) => day,
+            None => {
+                eprintln!("Unknown day: {}", name);
+                exit(1);
+            }
+        })
+        .collect()
+}
+
+fn run_bench(args: &Args) {
+    let iterations = match args.option("iterations").unwrap_or("5").parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            eprintln!("--iterations must be a positive number");
+            exit(1);
+        }
+    };
+    let days = selected_days(&args.positional[1..]);
+
+    budget::silent_panic_hook();
+    let mut failed = false;
+    println!(
+        "{:<6} {:<6} {:>12} {:>12} {:>12}",
+        "Day", "Phase", "Min", "Median", "Max"
+    );
+    for day in days.iter() {
+        // An explicit input only makes sense when benchmarking a single day.
+        let source = if days.len() == 1 {
+            input::Source::resolve(day, args.option("input"))
+        } else {
+            input::Source::File(day.data_path())
+        };
+        let result = source
+            .read()
+            .map_err(|e| e.to_string())
+            .and_then(|input| bench::bench_day(day, &input, iterations));
+        match result {
+            Ok(benchmark) => {
+                for (phase, stats) in &[
+                    ("parse", &benchmark.parse),
+                    ("part1", &benchmark.part1),
+                    ("part2", &benchmark.part2),
+                ] {
+                    println!(
+                        "{:<6} {:<6} {:>12} {:>12} {:>12}",
+                        day.name(),
+                        phase,
+                        format!("{:.2?}", stats.min),
+                        format!("{:.2?}", stats.median),
+                        format!("{:.2?}", stats.max)
+                    );
+                }
+            }
+            Err(error) => {
+                failed = true;
+                println!("{:<6} FAILED: {}", day.name(), error);
+            }
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+fn run_batch(args: &Args) {
+    let (day, dir) = match (
+        args.positional.get(1).and_then(|name| solver::find_day(name)),
+        args.positional.get(2),
+    ) {
+        (Some(day), Some(dir)) => (day, std::path::Path::new(dir)),
+        _ => {
+            eprintln!("batch needs a day and a directory, like `batch day7 inputs/`");
+            exit(1);
+        }
+    };
+
+    budget::silent_panic_hook();
+    let rows = match batch::run(day, dir, budget_from_args(args)) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("error: could not read {}: {}", dir.display(), e);
+            exit(1);
+        }
+    };
+    // With the CSV on stdout, the table goes to stderr so the CSV can be piped on its own.
+    if args.option("csv") == Some("-") {
+        eprint!("{}", batch::table(&rows));
+    } else {
+        print!("{}", batch::table(&rows));
+    }
+    if let Some(path) = args.option("csv") {
+        let csv = batch::to_csv(&rows);
+        let written = if path == "-" {
+            std::io::Write::write_all(&mut std::io::stdout(), csv.as_bytes())
+        } else {
+            std::fs::write(path, csv)
+        };
+        if let Err(e) = written {
+            eprintln!("error: could not write {}: {}", path, e);
+            exit(1);
+        }
+    }
+    if !rows.iter().all(|row| row.report.succeeded()) {
+        exit(1);
+    }
+}
+
+fn run_generate(args: &Args) {
+    let day = match args.positional.get(1).and_then(|name| solver::find_day(name)) {
+        Some(day) => day,
+        None => {
+            eprintln!("generate needs a day, like `generate day7`");
+            exit(1);
+        }
+    };
+    let seed = match args.option("seed").unwrap_or("1").parse::<u64>() {
+        Ok(seed) => seed,
+        Err(_) => {
+            eprintln!("--seed must be a number");
+            exit(1);
+        }
+    };
+    let size = match args.option("size").unwrap_or("100").parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            eprintln!("--size must be a positive number");
+            exit(1);
+        }
+    };
+    match generate::generate(day.number, seed, size) {
+        Some(input) => print!("{}", input),
+        None => {
+            eprintln!("No generator for {}", day.name());
+            exit(1);
+        }
+    }
+}
+
+fn run_fuzz(args: &Args) {
+    let targets = if args.positional.len() > 1 {
+        args.positional[1..]
+            .iter()
+            .map(|name| match fuzz::find_target(name) {
+                Some(target) => target,
+                None => {
+                    eprintln!("Unknown fuzz target: {}", name);
+                    exit(1);
+                }
+            })
+            .collect()
+    } else {
+        fuzz::targets()
+    };
+    let mut options = fuzz::Options::default();
+    if let Some(iterations) = args.option("iterations") {
+        options.iterations = match iterations.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => {
+                eprintln!("--iterations must be a number");
+                exit(1);
+            }
+        };
+    }
+    if let Some(seed) = args.option("seed") {
+        options.seed = match seed.parse::<u64>() {
+            Ok(seed) => seed,
+            Err(_) => {
+                eprintln!("--seed must be a number");
+                exit(1);
+            }
+        };
+    }
+    // Each input runs in a child process, so a parser that crashes is reported rather than taking
+    // the fuzzer down with it.
+    match isolate::Command::current_exe(&["fuzz-child"]) {
+        Ok(command) => options.isolation = Some(command),
+        Err(e) => eprintln!(
+            "warning: fuzzing in this process, where a crash ends the run: {}",
+            e
+        ),
+    }
+    let corpus = std::path::Path::new(args.option("corpus").unwrap_or("fuzz/corpus"));
+
+    budget::silent_panic_hook();
+    let mut failed = false;
+    for target in &targets {
+        // Saved failures are replayed first, as they were saved, then the lines or whole of the
+        // data file and some generated inputs.
+        let mut seeds = fuzz::load_corpus(corpus, target).unwrap_or_else(|e| {
+            eprintln!("Could not read the corpus for {}: {}", target.name, e);
+            exit(1);
+        });
+        let data = std::fs::read(format!("data/day{}.txt", target.day)).ok();
+        let generated = (0..3)
+            .filter_map(|seed| generate::generate(target.day, seed, 20).map(String::into_bytes));
+        seeds.extend(target.seeds(data.into_iter().chain(generated)));
+
+        let failures = fuzz::fuzz(target, &seeds, &options);
+        println!(
+            "{}: {} inputs, {} failures",
+            target.name,
+            seeds.len() + options.iterations,
+            failures.len()
+        );
+        for failure in &failures {
+            failed = true;
+            match failure.save(corpus) {
+                Ok(path) => println!("  {} (saved to {})", failure, path.display()),
+                Err(e) => println!("  {} (not saved: {})", failure, e),
+            }
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+// Parses stdin with one fuzz target, for `fuzz` to run in a child process. A panic's message goes
+// to stderr and the exit code says it panicked.
+fn run_fuzz_child(args: &Args) {
+    let name = match args.positional.get(1) {
+        Some(name) => name,
+        None => {
+            eprintln!("fuzz-child needs a target");
+            exit(1);
+        }
+    };
+    let mut input = Vec::new();
+    if let Err(e) = std::io::Read::read_to_end(&mut std::io::stdin(), &mut input) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+    budget::silent_panic_hook();
+    if let Err(message) = fuzz::child(name, input) {
+        eprint!("{}", message);
+        exit(isolate::PANICKED);
+    }
+}
+
+// Solves stdin as one day, for `serve` to run in a child process. Writes the response's status and
+// body to stdout.
+fn run_solve_child(args: &Args) {
+    let (name, timeout) = match (args.positional.get(1), args.positional.get(2)) {
+        (Some(name), Some(millis)) => match millis.parse::<u64>() {
+            Ok(millis) => (name, std::time::Duration::from_millis(millis)),
+            Err(_) => {
+                eprintln!("solve-child needs a timeout in milliseconds");
+                exit(1);
+            }
+        },
+        _ => {
+            eprintln!("solve-child needs a day and a timeout in milliseconds");
+            exit(1);
+        }
+    };
+    let mut input = Vec::new();
+    if let Err(e) = std::io::Read::read_to_end(&mut std::io::stdin(), &mut input) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+    budget::silent_panic_hook();
+    match server::child(name, timeout, input) {
+        Ok(output) => {
+            if let Err(e) = std::io::Write::write_all(&mut std::io::stdout(), &output) {
+                eprintln!("error: {}", e);
+                exit(1);
+            }
+        }
+        Err(message) => {
+            eprint!("{}", message);
+            exit(isolate::PANICKED);
+        }
+    }
+}
+
+fn run_serve(args: &Args) {
+    let port = match args.option("port").unwrap_or("8080").parse::<u16>() {
+        Ok(port) => port,
+        Err(_) => {
+            eprintln!("--port must be a port number");
+            exit(1);
+        }
+    };
+    let mut config = server::Config::default();
+    if let Some(max_body) = args.option("max-body") {
+        config.max_body = match max_body.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => {
+                eprintln!("--max-body must be a number of bytes");
+                exit(1);
+            }
+        };
+    }
+    if let Some(max_in_flight) = args.option("max-in-flight") {
+        config.max_in_flight = match max_in_flight.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                eprintln!("--max-in-flight must be a positive number");
+                exit(1);
+            }
+        };
+    }
+    if let Some(time) = budget_from_args(args).time {
+        config.timeout = time;
+    }
+    // Each solve runs in a child process, so one that crashes or will not stop cannot take the
+    // server with it.
+    match isolate::Command::current_exe(&["solve-child"]) {
+        Ok(command) => config.isolation = Some(command),
+        Err(e) => eprintln!(
+            "warning: solving in this process, where a crash stops the server: {}",
+            e
+        ),
+    }
+
+    let listener = match std::net::TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: could not listen on port {}: {}", port, e);
+            exit(1);
+        }
+    };
+    budget::silent_panic_hook();
+    println!("Listening on http://127.0.0.1:{}/", port);
+    if let Err(e) = server::serve(listener, config) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+fn run_fetch(args: &Args) {
+    let day = match args.positional.get(1).and_then(|name| solver::find_day(name)) {
+        Some(day) => day,
+        None => {
+            eprintln!("fetch needs a day, like `fetch day7`");
+            exit(1);
+        }
+    };
+    let mut config = fetch::Config {
+        force: args.flag("force"),
+        insecure: args.flag("insecure"),
+        ..fetch::Config::default()
+    };
+    if let Some(url) = args.option("base-url") {
+        config.base_url = url.to_string();
+    }
+    if let Some(path) = args.option("session") {
+        config.session_file = path.into();
+    }
+    match fetch::fetch(day.number, &config) {
+        Ok(fetch::Fetched::Cached(path)) => {
+            println!("{} is already saved; use --force to download it again", path.display())
+        }
+        Ok(fetch::Fetched::Downloaded(path)) => println!("Saved {}", path.display()),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+fn run_submit(args: &Args) {
+    let day = match args.positional.get(1).and_then(|name| solver::find_day(name)) {
+        Some(day) => day,
+        None => {
+            eprintln!("submit needs a day and a part, like `submit day7 part1`");
+            exit(1);
+        }
+    };
+    let part = match args.positional.get(2).map(String::as_str) {
+        Some("part1") => 1,
+        Some("part2") => 2,
+        _ => {
+            eprintln!("submit needs a part, like `submit day7 part1`");
+            exit(1);
+        }
+    };
+    let answer = match args.option("answer") {
+        Some(text) => answer::Answer::parse(text),
+        None => {
+            let source = input::Source::resolve(day, args.option("input"));
+            let mut input = match source.open() {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    exit(1);
+                }
+            };
+            budget::silent_panic_hook();
+            let report = runner::run_day_within(day, &mut input, budget_from_args(args));
+            let answer = if part == 1 { report.part1 } else { report.part2 };
+            match answer {
+                Some(answer) if answer.is_unsolved() => {
+                    eprintln!("error: {} part {} is not solved yet", day.name(), part);
+                    exit(1);
+                }
+                Some(answer) => answer,
+                None => {
+                    eprintln!(
+                        "error: {} part {} has no answer: {}",
+                        day.name(),
+                        part,
+                        report.error.unwrap_or_default()
+                    );
+                    exit(1);
+                }
+            }
+        }
+    };
+
+    let mut config = submit::Config {
+        insecure: args.flag("insecure"),
+        ..submit::Config::default()
+    };
+    if let Some(url) = args.option("base-url") {
+        config.base_url = url.to_string();
+    }
+    if let Some(path) = args.option("session") {
+        config.session_file = path.into();
+    }
+    if let Some(path) = args.option("history") {
+        config.history_file = path.into();
+    }
+    if let Some(cooldown) = args.option("cooldown") {
+        config.cooldown = match cooldown.parse::<u64>() {
+            Ok(seconds) => std::time::Duration::from_secs(seconds),
+            Err(_) => {
+                eprintln!("--cooldown must be a number of seconds");
+                exit(1);
+            }
+        };
+    }
+    println!("Submitting {} for {} part {}", answer, day.name(), part);
+    match submit::submit(day.number, part, &answer, &config) {
+        Ok(submit::Verdict::Correct) => println!("Correct!"),
+        Ok(verdict) => {
+            println!("Not accepted: {}", verdict);
+            exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+fn run_repl(args: &Args) {
+    let day = match args.positional.get(1).and_then(|name| solver::find_day(name)) {
+        Some(day) => day,
+        None => {
+            eprintln!("repl needs a day, like `repl day7`");
+            exit(1);
+        }
+    };
+    // Commands are read from stdin, so the input has to come from a file.
+    let source = match args.option("input") {
+        Some("-") => {
+            eprintln!("repl reads commands from stdin; give the input as a file");
+            exit(1);
+        }
+        Some(path) => input::Source::File(path.into()),
+        None => input::Source::File(day.data_path()),
+    };
+    let mut input = match source.open() {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    };
+    let mut session = match repl::start(day, &mut input) {
+        Ok(session) => session,
+        Err(error::Error::Parse(e)) => {
+            eprint!("{}", e.diagnostic(&source.to_string()));
+            exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    };
+
+    budget::silent_panic_hook();
+    println!("Loaded {}. Type `help` for commands.", source);
+    let prompt = format!("{}> ", day.name());
+    let result = repl::run(
+        session.as_mut(),
+        &mut editor::Editor::new(),
+        &prompt,
+        budget_from_args(args),
+    );
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+fn run_new_day(args: &Args) {
+    let number = match args.positional.get(1).map(|n| n.parse::<usize>()) {
+        Some(Ok(number)) => number,
+        _ => {
+            eprintln!("new-day needs a day number");
+            exit(1);
+        }
+    };
+    match scaffold::new_day(std::path::Path::new("."), number) {
+        Ok(changed) => {
+            for path in changed {
+                println!("wrote {}", path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+fn run_verify(args: &Args) {
+    let path = args.option("answers").unwrap_or(answers::DEFAULT_PATH);
+    let mut expected = match answers::Answers::load(path) {
+        Ok(answers) => answers,
+        Err(error::Error::Parse(e)) => {
+            eprint!("{}", e.diagnostic(path));
+            exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    };
+    let record = args.flag("record");
 
-    if args.len() < 1 {
-        println!("Usage example: adventofcode2020 day1 < input.txt");
-        panic!();
+    // Without a list of days, check every day with a known answer, or record every day.
+    let days = if args.positional.len() > 1 || record {
+        selected_days(&args.positional[1..])
+    } else {
+        expected
+            .days()
+            .into_iter()
+            .filter_map(|number| solver::DAYS.iter().find(|day| day.number == number))
+            .collect()
+    };
+    if days.is_empty() {
+        eprintln!("No answers in {}; run with --record to create it", path);
+        exit(1);
     }
 
-    let input = std::io::BufReader::new(std::io::stdin());
+    let budget = budget_from_args(args);
+    budget::silent_panic_hook();
+    let (mut mismatches, mut failed) = (0, 0);
+    for day in days {
+        let report = runner::run_day_from_data(day, budget);
+        if record {
+            expected.record(&report);
+            if let Some(error) = &report.error {
+                eprintln!("{}: not recording failed parts: {}", day.name(), error);
+            }
+            continue;
+        }
 
-    match args[1].as_str() {
-        "day1" => {
-            let input = day1::read_input(input).unwrap();
-            day1::day1(input.as_slice());
+        // A part that fails is a failure even when there is no answer to check it against.
+        let failures = expected.check(&report);
+        if failures.is_empty() && report.succeeded() {
+            println!("{} ok", day.name());
+            continue;
         }
-        "day2" => {
-            day2::run(input).unwrap();
+        mismatches += failures.len();
+        for failure in failures {
+            print!("{}", failure);
         }
-        "day3" => {
-            day3::run(input).unwrap();
+        if let Some(error) = &report.error {
+            failed += 1;
+            println!("{} failed: {}", day.name(), error);
         }
-        "day4" => {
-            day4::run(input).unwrap();
+    }
+
+    if record {
+        if let Err(e) = expected.save(path) {
+            eprintln!("error: could not write {}: {}", path, e);
+            exit(1);
         }
-        "day5" => {
-            day5::run(input).unwrap();
+        println!("Recorded answers in {}", path);
+    } else if mismatches > 0 || failed > 0 {
+        if mismatches > 0 {
+            println!("{} answer(s) did not match {}", mismatches, path);
         }
-        "day6" => {
-            day6::run(input).unwrap();
+        if failed > 0 {
+            println!("{} day(s) failed", failed);
         }
-        "day7" => {
-            day7::run(input).unwrap();
+        exit(1);
+    }
+}
+
+fn run_one(name: &str, args: &Args) {
+    let day = match solver::find_day(name) {
+        Some(day) => day,
+        None => {
+            println!("Unknown day: {}", name);
+            exit(1);
         }
-        "day8" => {
-            day8::run(input).unwrap();
+    };
+
+    let source = input::Source::resolve(day, args.option("input"));
+    let input = match source.read() {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
         }
-        "day9" => {
-            day9::run(input).unwrap();
+    };
+    let budget = budget_from_args(args);
+    // Answers that come from the cache have no steps to explain.
+    let mut cache = if args.flag("explain") {
+        None
+    } else {
+        load_cache(args)
+    };
+    if Format::from_args(args) == Format::Json {
+        budget::silent_panic_hook();
+        let report = match &mut cache {
+            Some(cache) => cache::run_day(cache, day, &input, budget),
+            None => runner::run_day_within(day, &mut &input[..], budget),
+        };
+        save_cache(cache.as_ref());
+        println!("{}", report.to_json());
+        if !report.succeeded() {
+            exit(1);
         }
-        "day10" => {
-            day10::run(input).unwrap();
+        return;
+    }
+
+    budget::quiet_panic_hook();
+    // The input is only parsed if a part has to be solved.
+    let mut solver = None;
+    for part in 1..=2 {
+        let key = cache::Key::new(day, part, &input);
+        let cached = cache.as_ref().and_then(|cache| cache.get(&key)).cloned();
+        let answer = match cached {
+            Some(answer) => answer,
+            None => {
+                let solver = solver.get_or_insert_with(|| {
+                    match within_budget(
+                        "parse",
+                        budget::limit(budget, || day.parse(&mut &input[..])),
+                    ) {
+                        Ok(solver) => solver,
+                        Err(error::Error::Parse(e)) => {
+                            eprint!("{}", e.diagnostic(&source.to_string()));
+                            exit(1);
+                        }
+                        Err(e) => {
+                            eprintln!("error: {}", e);
+                            exit(1);
+                        }
+                    }
+                });
+                let phase = format!("part {}", part);
+                let answer = within_budget(
+                    &phase,
+                    budget::limit(budget, || {
+                        if part == 1 {
+                            solver.part1()
+                        } else {
+                            solver.part2()
+                        }
+                    }),
+                );
+                if let Some(cache) = &mut cache {
+                    cache.insert(key, answer.clone());
+                }
+                answer
+            }
+        };
+        println!("Part {}: {}", part, answer);
+    }
+    save_cache(cache.as_ref());
+}
+
+// Loads the answer cache, unless `--no-cache` says not to use it. A cache that cannot be read is
+// started afresh.
+fn load_cache(args: &Args) -> Option<cache::Cache> {
+    if args.flag("no-cache") {
+        return None;
+    }
+    match cache::Cache::load(std::path::Path::new(cache::DEFAULT_PATH)) {
+        Ok(cache) => Some(cache),
+        Err(e) => {
+            eprintln!("warning: ignoring the answer cache: {}", e);
+            Some(cache::Cache::default())
         }
-        "day11" => {
-            day11::run(input).unwrap();
+    }
+}
+
+// Saves the answer cache if anything in it changed. Failing to is not worth failing the run for.
+fn save_cache(cache: Option<&cache::Cache>) {
+    if let Some(cache) = cache.filter(|cache| cache.changed()) {
+        if let Err(e) = cache.save(std::path::Path::new(cache::DEFAULT_PATH)) {
+            eprintln!("warning: could not save the answer cache: {}", e);
         }
-        "day12" => {
-            day12::run(input).unwrap();
+    }
+}
+
+fn run_cache(args: &Args) {
+    match args.positional.get(1).map(String::as_str) {
+        Some("clear") => match cache::clear(std::path::Path::new(cache::DEFAULT_PATH)) {
+            Ok(0) => println!("The answer cache is already empty"),
+            Ok(count) => println!("Removed {} cached answers", count),
+            Err(e) => {
+                eprintln!("error: could not clear {}: {}", cache::DEFAULT_PATH, e);
+                exit(1);
+            }
+        },
+        _ => {
+            eprintln!("cache needs a subcommand, like `cache clear`");
+            exit(1);
         }
-        "day13" => {
-            day13::run(input).unwrap();
+    }
+}
+
+// Unwraps the result of one phase of a run, exiting if it ran out of budget.
+fn within_budget<T>(phase: &str, result: Result<T, budget::Exceeded>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(exceeded) => {
+            eprintln!("error: {} {}", phase, exceeded);
+            exit(1);
         }
-        "day14" => {
-            day14::run(input).unwrap();
+    }
+}
+
+// Reads `--timeout SECONDS` and `--max-steps N`, which limit each phase of a day's run.
+fn budget_from_args(args: &Args) -> budget::Budget {
+    let time = args.option("timeout").map(|seconds| match seconds.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => {
+            std::time::Duration::from_secs_f64(seconds)
         }
-        "day15" => {
-            day15::run();
+        _ => {
+            eprintln!("--timeout must be a positive number of seconds");
+            exit(1);
         }
-        "day16" => {
-            day16::run(input).unwrap();
+    });
+    let steps = args.option("max-steps").map(|steps| match steps.parse::<u64>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            eprintln!("--max-steps must be a positive number");
+            exit(1);
         }
-        day => {
-            println!("Unknown day: {}", day);
+    });
+    budget::Budget { time, steps }
+}
+
+fn print_table(reports: &[runner::Report]) {
+    let rows: Vec<[String; 4]> = reports
+        .iter()
+        .map(|report| {
+            [
+                format!("day{}", report.day),
+                report.part1.as_ref().map_or("-".to_string(), ToString::to_string),
+                report.part2.as_ref().map_or("-".to_string(), ToString::to_string),
+                match &report.error {
+                    None => "ok".into(),
+                    // Panic messages can span several lines; keep each report on one row.
+                    Some(error) => format!(
+                        "FAILED: {}",
+                        error.split_whitespace().collect::<Vec<_>>().join(" ")
+                    ),
+                },
+            ]
+        })
+        .collect();
+
+    let header = ["Day", "Part 1", "Part 2", "Status"].map(String::from);
+    let mut widths = [0; 4];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
         }
     }
+
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        println!(
+            "{:<w0$}  {:>w1$}  {:>w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
 }
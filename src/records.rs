@@ -0,0 +1,175 @@
+use std::io::BufRead;
+
+/// A group of consecutive non-blank lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// The number of the record's first line, counting from 1.
+    pub line: usize,
+    /// The record's lines, without line endings or trailing whitespace.
+    pub lines: Vec<String>,
+    /// The header of the section this record is in, if any.
+    pub section: Option<String>,
+}
+
+impl Record {
+    /// The record's lines, each with its line number.
+    pub fn numbered(&self) -> impl Iterator<Item = (usize, &str)> {
+        (self.line..).zip(self.lines.iter().map(String::as_str))
+    }
+
+    /// The record's lines, each followed by a newline.
+    pub fn text(&self) -> String {
+        self.lines.iter().map(|line| line.clone() + "\n").collect()
+    }
+}
+
+/// Reads blank-line-separated records from a `BufRead`, one at a time.
+///
+/// Lines may end in `\n` or `\r\n`, and the last one need not end at all. Trailing whitespace is
+/// dropped, so a line of only spaces counts as blank.
+///
+/// Inputs such as day 16's are split into named sections, each starting with a header line like
+/// `your ticket:`. With `with_sections`, a header ends the record before it and starts a new one,
+/// which is returned even if it is empty. Every record up to the next header carries its name.
+pub struct Records<R> {
+    lines: std::io::Lines<R>,
+    line_number: usize,
+    sections: &'static [&'static str],
+    section: Option<String>,
+    pending: Option<Record>,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(input: R) -> Records<R> {
+        Records {
+            lines: input.lines(),
+            line_number: 0,
+            sections: &[],
+            section: None,
+            pending: None,
+        }
+    }
+
+    pub fn with_sections(mut self, headers: &'static [&'static str]) -> Records<R> {
+        self.sections = headers;
+        self
+    }
+
+    /// How many lines have been read so far.
+    pub fn lines_read(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = std::io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = self.pending.take();
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => return record.map(Ok),
+            };
+            self.line_number += 1;
+            let line = line.trim_end();
+
+            if self.sections.contains(&line) {
+                self.section = Some(line.to_string());
+                let header = Record {
+                    line: self.line_number + 1,
+                    lines: Vec::new(),
+                    section: self.section.clone(),
+                };
+                match record {
+                    Some(record) => {
+                        self.pending = Some(header);
+                        return Some(Ok(record));
+                    }
+                    None => record = Some(header),
+                }
+            } else if line.is_empty() {
+                if record.is_some() {
+                    return record.map(Ok);
+                }
+            } else {
+                record
+                    .get_or_insert_with(|| Record {
+                        line: self.line_number,
+                        lines: Vec::new(),
+                        section: self.section.clone(),
+                    })
+                    .lines
+                    .push(line.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(input: &str) -> Vec<Record> {
+        Records::new(input.as_bytes())
+            .collect::<std::io::Result<_>>()
+            .unwrap()
+    }
+
+    fn record(line: usize, lines: &[&str], section: Option<&str>) -> Record {
+        Record {
+            line,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            section: section.map(String::from),
+        }
+    }
+
+    #[test]
+    fn split_on_blank_lines() {
+        assert_eq!(
+            read("a\nb\n\n\nc  \n \nd"),
+            vec![
+                record(1, &["a", "b"], None),
+                record(5, &["c"], None),
+                record(7, &["d"], None),
+            ]
+        );
+        assert_eq!(read(""), vec![]);
+        assert_eq!(read("\n\n"), vec![]);
+    }
+
+    #[test]
+    fn crlf() {
+        assert_eq!(
+            read("a\r\nb\r\n\r\nc\r\n"),
+            vec![record(1, &["a", "b"], None), record(4, &["c"], None)]
+        );
+    }
+
+    #[test]
+    fn numbered_lines() {
+        let records = read("\nx\ny\n");
+        let numbered: Vec<(usize, &str)> = records[0].numbered().collect();
+        assert_eq!(numbered, vec![(2, "x"), (3, "y")]);
+        assert_eq!(records[0].text(), "x\ny\n");
+    }
+
+    #[test]
+    fn sections() {
+        let input = "rule\n\nmine:\n1\nothers:\n2\n\n3\n\nmine:\n";
+        let mut records = Records::new(input.as_bytes()).with_sections(&["mine:", "others:"]);
+        let all: Vec<Record> = records.by_ref().map(Result::unwrap).collect();
+        assert_eq!(
+            all,
+            vec![
+                record(1, &["rule"], None),
+                record(4, &["1"], Some("mine:")),
+                record(6, &["2"], Some("others:")),
+                record(8, &["3"], Some("others:")),
+                record(11, &[], Some("mine:")),
+            ]
+        );
+        assert_eq!(records.lines_read(), 10);
+    }
+}
@@ -0,0 +1,689 @@
+//! `repl`: parses a day's input once and answers questions about it.
+//!
+//! Every day understands `part1` and `part2`. Days whose parsed input is worth poking at have
+//! commands of their own: the bag rules of day 7, the handheld console of day 8, the docking
+//! program of day 14 and the ticket notes of day 16.
+
+use crate::budget::{self, Budget};
+use crate::editor::Editor;
+use crate::error::Result;
+use crate::runner;
+use crate::solver::{Day, Solver};
+use crate::{day14, day16, day7, day8};
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+
+/// A parsed input being explored.
+pub trait Session {
+    fn solver(&self) -> &dyn Solver;
+
+    /// The commands only this day understands, as usage and description.
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Runs one of this day's own commands, or returns `None` if `command` is not one of them.
+    fn run(
+        &mut self,
+        _command: &str,
+        _argument: &str,
+    ) -> Option<std::result::Result<String, String>> {
+        None
+    }
+}
+
+/// Parses `input` for `day` and starts a session on it.
+pub fn start(day: &Day, input: &mut dyn BufRead) -> Result<Box<dyn Session>> {
+    let session: Box<dyn Session> = match day.number {
+        7 => Box::new(Bags(day7::Rules::parse(input)?)),
+        8 => Box::new(Console::new(day8::Machine::parse(input)?)),
+        14 => Box::new(Docking::new(day14::Program::parse(input)?)),
+        16 => Box::new(Tickets(day16::Problem::parse(input)?)),
+        _ => Box::new(Plain(day.parse(input)?)),
+    };
+    Ok(session)
+}
+
+/// What a line typed at the prompt came to.
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Output(String),
+    Failed(String),
+    Quit,
+}
+
+/// Runs the command on `line`. Each command may only use `budget`.
+pub fn execute(session: &mut dyn Session, line: &str, budget: Budget) -> Reply {
+    let line = line.trim();
+    let (command, argument) = match line.find(' ') {
+        Some(i) => (&line[..i], line[i + 1..].trim()),
+        None => (line, ""),
+    };
+    match command {
+        "quit" | "exit" => return Reply::Quit,
+        "help" => return Reply::Output(help(session)),
+        _ => {}
+    }
+
+    let outcome = runner::catch(|| {
+        budget::limit(budget, || match command {
+            "" => Some(Ok(String::new())),
+            "part1" => Some(Ok(session.solver().part1().to_string())),
+            "part2" => Some(Ok(session.solver().part2().to_string())),
+            _ => session.run(command, argument),
+        })
+    });
+    match outcome {
+        Ok(Ok(Some(Ok(output)))) => Reply::Output(output),
+        Ok(Ok(Some(Err(error)))) => Reply::Failed(error),
+        Ok(Ok(None)) => Reply::Failed(format!("unknown command `{}`; try `help`", command)),
+        Ok(Err(exceeded)) => Reply::Failed(exceeded.to_string()),
+        Err(panic) => Reply::Failed(format!("panicked: {}", panic)),
+    }
+}
+
+/// Reads commands with `editor` until the input ends or one says to quit, printing each answer as
+/// soon as it is known.
+pub fn run(
+    session: &mut dyn Session,
+    editor: &mut Editor,
+    prompt: &str,
+    budget: Budget,
+) -> io::Result<()> {
+    while let Some(line) = editor.read_line(prompt)? {
+        match execute(session, &line, budget) {
+            Reply::Output(output) if output.is_empty() => {}
+            Reply::Output(output) => println!("{}", output),
+            Reply::Failed(error) => eprintln!("error: {}", error),
+            Reply::Quit => break,
+        }
+    }
+    Ok(())
+}
+
+fn help(session: &dyn Session) -> String {
+    let common = [
+        ("part1", "the answer to part 1"),
+        ("part2", "the answer to part 2"),
+        ("help", "this list"),
+        ("quit", "leaves"),
+    ];
+    let commands: Vec<&(&str, &str)> = session.commands().iter().chain(common.iter()).collect();
+    let width = commands
+        .iter()
+        .map(|(usage, _)| usage.len())
+        .max()
+        .unwrap_or(0);
+    commands
+        .iter()
+        .map(|(usage, description)| format!("{:<width$}  {}", usage, description, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Any day, with nothing but its answers.
+struct Plain(Box<dyn Solver>);
+
+impl Session for Plain {
+    fn solver(&self) -> &dyn Solver {
+        self.0.as_ref()
+    }
+}
+
+// Day 7's bag rules.
+struct Bags(day7::Rules);
+
+impl Bags {
+    // Accepts a bag as written in the rules, with or without the word "bag".
+    fn bag<'a>(&self, argument: &'a str) -> std::result::Result<&'a str, String> {
+        let bag = argument.trim_end_matches(" bags").trim_end_matches(" bag");
+        let graph = &self.0 .0;
+        let known = graph.contains_key(bag)
+            || graph
+                .values()
+                .any(|contents| contents.iter().any(|(_, inner)| inner == bag));
+        if bag.is_empty() {
+            Err("which bag?".to_string())
+        } else if known {
+            Ok(bag)
+        } else {
+            Err(format!("no rule mentions {} bags", bag))
+        }
+    }
+}
+
+impl Session for Bags {
+    fn solver(&self) -> &dyn Solver {
+        &self.0
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            (
+                "contains <bag>",
+                "what <bag> holds, and how many bags that is in all",
+            ),
+            ("holders <bag>", "the bags that hold <bag>, however deeply"),
+        ]
+    }
+
+    fn run(
+        &mut self,
+        command: &str,
+        argument: &str,
+    ) -> Option<std::result::Result<String, String>> {
+        let graph = &self.0 .0;
+        match command {
+            "contains" => Some(self.bag(argument).map(|bag| {
+                let contents = graph.get(bag).map_or(&[][..], Vec::as_slice);
+                if contents.is_empty() {
+                    return format!("{} bags hold no other bags", bag);
+                }
+                let mut lines: Vec<String> = contents
+                    .iter()
+                    .map(|(count, inner)| format!("{} {}", count, inner))
+                    .collect();
+                lines.push(format!("{} bags in all", day7::bags_inside(graph, bag)));
+                lines.join("\n")
+            })),
+            "holders" => Some(self.bag(argument).map(|bag| {
+                let mut holders: Vec<String> = day7::holders(graph, bag).into_iter().collect();
+                if holders.is_empty() {
+                    return format!("no bag holds {} bags", bag);
+                }
+                holders.sort();
+                holders.push(format!("{} bags in all", holders.len()));
+                holders.join("\n")
+            })),
+            _ => None,
+        }
+    }
+}
+
+// Day 8's handheld console, stepped through by hand.
+struct Console {
+    program: day8::Machine,
+    machine: day8::Machine,
+    // The instructions run since the last reset.
+    visited: HashSet<isize>,
+}
+
+impl Console {
+    fn new(program: day8::Machine) -> Console {
+        Console {
+            machine: program.clone(),
+            program,
+            visited: HashSet::new(),
+        }
+    }
+
+    // Runs the next instruction, describing it, or says why there is none.
+    fn step(&mut self) -> std::result::Result<String, String> {
+        if self.machine.terminated() {
+            return Err(format!(
+                "the program has ended; acc = {}",
+                self.machine.acc()
+            ));
+        }
+        let ip = self.machine.ip();
+        let instruction = match self.machine.next_instruction() {
+            Some(instruction) => instruction.to_string(),
+            None => return Err(format!("ip {} is outside the program", ip)),
+        };
+        budget::spend(|| format!("stepping through ip {}", ip));
+        let repeated = !self.visited.insert(ip);
+        self.machine.step();
+        Ok(format!(
+            "{:>4}: {:<8} acc = {}{}",
+            ip,
+            instruction,
+            self.machine.acc(),
+            if repeated { " (repeated)" } else { "" }
+        ))
+    }
+}
+
+impl Session for Console {
+    fn solver(&self) -> &dyn Solver {
+        &self.program
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("step [n]", "runs the next n instructions, 1 by default"),
+            (
+                "run",
+                "runs until the program ends or is about to repeat itself",
+            ),
+            ("acc", "the accumulator"),
+            ("ip", "the instruction pointer, and the instruction there"),
+            ("reset", "starts the program again"),
+        ]
+    }
+
+    fn run(
+        &mut self,
+        command: &str,
+        argument: &str,
+    ) -> Option<std::result::Result<String, String>> {
+        let result = match command {
+            "step" => count(argument).and_then(|n| {
+                let mut lines = Vec::new();
+                for _ in 0..n {
+                    match self.step() {
+                        Ok(line) => lines.push(line),
+                        Err(e) if lines.is_empty() => return Err(e),
+                        Err(e) => {
+                            lines.push(e);
+                            break;
+                        }
+                    }
+                }
+                Ok(lines.join("\n"))
+            }),
+            "run" => loop {
+                if self.machine.terminated() {
+                    break Ok(format!("the program ended; acc = {}", self.machine.acc()));
+                }
+                let ip = self.machine.ip();
+                if self.visited.contains(&ip) {
+                    break Ok(format!(
+                        "ip {} is about to run again; acc = {}",
+                        ip,
+                        self.machine.acc()
+                    ));
+                }
+                if let Err(e) = self.step() {
+                    break Err(e);
+                }
+            },
+            "acc" => Ok(self.machine.acc().to_string()),
+            "ip" => Ok(match self.machine.next_instruction() {
+                Some(instruction) => format!("{}: {}", self.machine.ip(), instruction),
+                None => format!("{}: outside the program", self.machine.ip()),
+            }),
+            "reset" => {
+                self.machine.reset();
+                self.visited.clear();
+                Ok(String::new())
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+// Day 14's docking program, run an instruction at a time.
+struct Docking {
+    program: day14::Program,
+    machine: day14::Machine,
+    // The index of the next instruction to run.
+    next: usize,
+}
+
+impl Docking {
+    fn new(program: day14::Program) -> Docking {
+        Docking {
+            program,
+            machine: day14::Machine::new(),
+            next: 0,
+        }
+    }
+
+    fn restart(&mut self, version2: bool) {
+        self.machine = day14::Machine::new();
+        if version2 {
+            self.machine.set_version2();
+        }
+        self.next = 0;
+    }
+}
+
+impl Session for Docking {
+    fn solver(&self) -> &dyn Solver {
+        &self.program
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("step [n]", "runs the next n instructions, 1 by default"),
+            ("mask", "the current mask"),
+            ("mem <address>", "the value at <address>"),
+            ("sum", "the sum of all of memory"),
+            ("version <1|2>", "switches decoder chip and starts again"),
+            ("reset", "starts the program again"),
+        ]
+    }
+
+    fn run(
+        &mut self,
+        command: &str,
+        argument: &str,
+    ) -> Option<std::result::Result<String, String>> {
+        let result = match command {
+            "step" => count(argument).map(|n| {
+                let mut lines = Vec::new();
+                for _ in 0..n {
+                    let instruction = match self.program.0.get(self.next) {
+                        Some(instruction) => instruction,
+                        None => {
+                            lines.push("the program has ended".to_string());
+                            break;
+                        }
+                    };
+                    self.machine.execute_program(std::iter::once(instruction));
+                    lines.push(format!("{:>4}: {}", self.next + 1, instruction));
+                    self.next += 1;
+                }
+                lines.join("\n")
+            }),
+            "mask" => Ok(match self.machine.mask() {
+                "" => "no mask has been set yet".to_string(),
+                mask => {
+                    let bits = |bit| mask.chars().filter(|&c| c == bit).count();
+                    format!(
+                        "mask = {}\n{} ones, {} zeros, {} floating",
+                        mask,
+                        bits('1'),
+                        bits('0'),
+                        bits('X')
+                    )
+                }
+            }),
+            "mem" => argument
+                .parse::<u64>()
+                .map(|address| self.machine.read(address).to_string())
+                .map_err(|_| format!("`{}` is not an address", argument)),
+            "sum" => Ok(self.machine.sum_memory().to_string()),
+            "version" => match argument {
+                "1" | "2" => {
+                    self.restart(argument == "2");
+                    Ok(String::new())
+                }
+                _ => Err("the version is 1 or 2".to_string()),
+            },
+            "reset" => {
+                self.restart(self.machine.is_version2());
+                Ok(String::new())
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+// Day 16's ticket notes.
+struct Tickets(day16::Problem);
+
+impl Session for Tickets {
+    fn solver(&self) -> &dyn Solver {
+        &self.0
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("fields", "the fields and their valid ranges"),
+            (
+                "candidates <field>",
+                "the columns where <field> fits every valid ticket",
+            ),
+            ("ticket", "your ticket"),
+        ]
+    }
+
+    fn run(
+        &mut self,
+        command: &str,
+        argument: &str,
+    ) -> Option<std::result::Result<String, String>> {
+        let problem = &self.0;
+        let result = match command {
+            "fields" => Ok(problem
+                .constraints
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{}: {}-{} or {}-{}",
+                        c.field_name,
+                        c.ranges[0].start(),
+                        c.ranges[0].end(),
+                        c.ranges[1].start(),
+                        c.ranges[1].end()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "candidates" => match problem
+                .constraints
+                .iter()
+                .find(|c| c.field_name == argument)
+            {
+                Some(constraint) => Ok(format!(
+                    "{}: {:?}",
+                    argument,
+                    problem.find_candidate_assignments(constraint)
+                )),
+                None => Err(format!("no field is called `{}`", argument)),
+            },
+            "ticket" => Ok(format!("{:?}", problem.my_ticket)),
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+// Reads the optional count of a `step` command.
+fn count(argument: &str) -> std::result::Result<usize, String> {
+    if argument.is_empty() {
+        return Ok(1);
+    }
+    argument
+        .parse()
+        .map_err(|_| format!("`{}` is not a number of steps", argument))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+
+    fn session(day: &str, input: &str) -> Box<dyn Session> {
+        start(find_day(day).unwrap(), &mut input.as_bytes()).unwrap()
+    }
+
+    fn output(session: &mut dyn Session, line: &str) -> String {
+        match execute(session, line, Budget::default()) {
+            Reply::Output(output) => output,
+            other => panic!("{}: {:?}", line, other),
+        }
+    }
+
+    fn failure(session: &mut dyn Session, line: &str) -> String {
+        match execute(session, line, Budget::default()) {
+            Reply::Failed(error) => error,
+            other => panic!("{}: {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn common_commands() {
+        let mut session = session("day1", "1721\n979\n366\n299\n675\n1456\n");
+        let session = session.as_mut();
+        assert_eq!(output(session, "part1"), "514579");
+        assert_eq!(output(session, "  part2 "), "241861950");
+        assert_eq!(output(session, ""), "");
+        assert!(output(session, "help").starts_with("part1  the answer to part 1\n"));
+        assert_eq!(
+            failure(session, "step"),
+            "unknown command `step`; try `help`"
+        );
+        assert_eq!(execute(session, "quit", Budget::default()), Reply::Quit);
+
+        let input = &mut "1721\nabc\n".as_bytes();
+        assert!(start(find_day("day1").unwrap(), input).is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let mut session = session("day13", "10\n4,6\n");
+        let budget = Budget {
+            steps: Some(100),
+            ..Budget::default()
+        };
+        match execute(session.as_mut(), "part2", budget) {
+            Reply::Failed(error) => assert!(error.starts_with("budget exceeded"), "{}", error),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn bags() {
+        let mut session = session(
+            "day7",
+            "light red bags contain 1 bright white bag, 2 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.",
+        );
+        let session = session.as_mut();
+        assert_eq!(
+            output(session, "contains shiny gold"),
+            "1 dark olive\n2 vibrant plum\n32 bags in all"
+        );
+        assert_eq!(
+            output(session, "contains faded blue bags"),
+            "faded blue bags hold no other bags"
+        );
+        assert_eq!(
+            output(session, "holders shiny gold bag"),
+            "bright white\nlight red\nmuted yellow\n3 bags in all"
+        );
+        assert_eq!(
+            output(session, "holders light red"),
+            "no bag holds light red bags"
+        );
+        assert_eq!(
+            failure(session, "contains plaid"),
+            "no rule mentions plaid bags"
+        );
+        assert!(output(session, "help").starts_with("contains <bag>  "));
+    }
+
+    #[test]
+    fn console() {
+        let mut session = session(
+            "day8",
+            "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n",
+        );
+        let session = session.as_mut();
+        assert_eq!(output(session, "ip"), "0: nop +0");
+        assert_eq!(
+            output(session, "step 2"),
+            "   0: nop +0   acc = 0\n   1: acc +1   acc = 1"
+        );
+        assert_eq!(output(session, "acc"), "1");
+        assert_eq!(
+            output(session, "run"),
+            "ip 1 is about to run again; acc = 5"
+        );
+        assert_eq!(output(session, "step"), "   1: acc +1   acc = 6 (repeated)");
+        assert_eq!(output(session, "reset"), "");
+        assert_eq!(output(session, "acc"), "0");
+        assert_eq!(failure(session, "step x"), "`x` is not a number of steps");
+
+        let mut session = self::session("day8", "acc +2\njmp +1\n");
+        let session = session.as_mut();
+        assert_eq!(output(session, "run"), "the program ended; acc = 2");
+        assert_eq!(failure(session, "step"), "the program has ended; acc = 2");
+
+        let mut session = self::session("day8", "jmp +5\n");
+        assert_eq!(
+            output(session.as_mut(), "step 3"),
+            "   0: jmp +5   acc = 0\nip 5 is outside the program"
+        );
+    }
+
+    #[test]
+    fn docking() {
+        let mut session = session(
+            "day14",
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        );
+        let session = session.as_mut();
+        assert_eq!(output(session, "mask"), "no mask has been set yet");
+        assert_eq!(
+            output(session, "step 2"),
+            "   1: mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n   2: mem[8] = 11"
+        );
+        assert_eq!(
+            output(session, "mask"),
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n1 ones, 1 zeros, 34 floating"
+        );
+        assert_eq!(output(session, "mem 8"), "73");
+        assert_eq!(
+            output(session, "step 5"),
+            "   3: mem[7] = 101\n   4: mem[8] = 0\nthe program has ended"
+        );
+        assert_eq!(output(session, "sum"), "165");
+        assert_eq!(failure(session, "mem x"), "`x` is not an address");
+
+        assert_eq!(failure(session, "version 3"), "the version is 1 or 2");
+
+        let mut session = self::session(
+            "day14",
+            "mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        );
+        let session = session.as_mut();
+        assert_eq!(output(session, "version 2"), "");
+        output(session, "step 2");
+        // Version 2 writes to every address the mask decodes 42 to.
+        assert_eq!(output(session, "mem 26"), "100");
+        assert_eq!(output(session, "mem 59"), "100");
+        output(session, "step 2");
+        assert_eq!(output(session, "sum"), "208");
+        assert_eq!(output(session, "reset"), "");
+        assert_eq!(output(session, "sum"), "0");
+        output(session, "step 4");
+        assert_eq!(output(session, "sum"), "208");
+    }
+
+    #[test]
+    fn tickets() {
+        let mut session = session(
+            "day16",
+            "class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9",
+        );
+        let session = session.as_mut();
+        assert_eq!(
+            output(session, "fields"),
+            "class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19"
+        );
+        assert_eq!(output(session, "candidates seat"), "seat: [2]");
+        assert_eq!(output(session, "candidates class"), "class: [1, 2]");
+        assert_eq!(output(session, "ticket"), "[11, 12, 13]");
+        assert_eq!(
+            failure(session, "candidates zone"),
+            "no field is called `zone`"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+/// A small, seedable pseudo-random number generator (SplitMix64).
+///
+/// It is not suitable for anything that needs real randomness, but the same seed always gives the
+/// same sequence on every platform, which is what reproducible generated inputs need.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, which must not be empty.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot pick from an empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `low..=high`.
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    /// True with probability `numerator / denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let a: Vec<u64> = (0..5)
+            .map({
+                let mut rng = Rng::new(7);
+                move |_| rng.next_u64()
+            })
+            .collect();
+        let mut rng = Rng::new(7);
+        let b: Vec<u64> = (0..5).map(|_| rng.next_u64()).collect();
+        assert_eq!(a, b);
+        assert_ne!(Rng::new(8).next_u64(), a[0]);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let n = rng.between(3, 5);
+            assert!((3..=5).contains(&n));
+        }
+        let mut items = vec![1, 2, 3, 4, 5];
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::answer::Answer;
+use crate::budget::{self, Budget};
+use crate::input::Source;
+use crate::json::Json;
+use crate::solver::{Day, DAYS};
+use std::io::BufRead;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The outcome of running one day's solver over its input.
+///
+/// A part's answer is `None` if it was never computed, either because parsing failed or because
+/// that part panicked. `error` describes the first failure, if any, and `failure` says what kind
+/// of failure it was.
+pub struct Report {
+    pub day: usize,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+    pub error: Option<String>,
+    pub failure: Option<Failure>,
+    pub times: Times,
+}
+
+/// Why a run failed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Failure {
+    /// The input could not be read or parsed.
+    Input,
+    /// A solver panicked, or its worker died.
+    Panic,
+    /// A phase ran out of budget.
+    Budget,
+}
+
+/// How long each phase of a run took. Phases that did not run take no time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Times {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+impl Report {
+    pub fn new(day: usize) -> Report {
+        Report {
+            day,
+            part1: None,
+            part2: None,
+            error: None,
+            failure: None,
+            times: Times::default(),
+        }
+    }
+
+    /// A report for a run that failed before getting anywhere.
+    pub fn failed(day: usize, failure: Failure, error: String) -> Report {
+        let mut report = Report::new(day);
+        report.fail(failure, error);
+        report
+    }
+
+    /// Records a failure, unless an earlier one has already been recorded.
+    pub fn fail(&mut self, failure: Failure, error: String) {
+        if self.error.is_none() {
+            self.error = Some(error);
+            self.failure = Some(failure);
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("day", Json::from(self.day)),
+            (
+                "part1",
+                Json::from(self.part1.as_ref().map(Answer::to_json)),
+            ),
+            (
+                "part2",
+                Json::from(self.part2.as_ref().map(Answer::to_json)),
+            ),
+            ("error", Json::from(self.error.clone())),
+            (
+                "timing",
+                Json::object(vec![
+                    ("parse_ns", Json::from(self.times.parse.as_nanos())),
+                    ("part1_ns", Json::from(self.times.part1.as_nanos())),
+                    ("part2_ns", Json::from(self.times.part2.as_nanos())),
+                ]),
+            ),
+        ])
+    }
+}
+
+/// Parses `input` and runs both parts, turning I/O errors and panics into a failed report.
+pub fn run_day(day: &Day, input: &mut dyn BufRead) -> Report {
+    run_day_within(day, input, Budget::default())
+}
+
+/// Like `run_day`, but parsing and each part may only use `budget`. A phase that runs out stops
+/// with an error saying how far it got.
+pub fn run_day_within(day: &Day, input: &mut dyn BufRead, budget: Budget) -> Report {
+    let mut report = Report::new(day.number);
+
+    let (solver, time) = phase("parse", budget, || day.parse(input));
+    report.times.parse = time;
+    let solver = match solver {
+        Ok(Ok(solver)) => solver,
+        Ok(Err(e)) => {
+            report.fail(Failure::Input, format!("parse failed: {}", e));
+            return report;
+        }
+        Err((failure, message)) => {
+            report.fail(failure, message);
+            return report;
+        }
+    };
+
+    let (answer, time) = phase("part 1", budget, || solver.part1());
+    report.times.part1 = time;
+    match answer {
+        Ok(answer) => report.part1 = Some(answer),
+        Err((failure, message)) => report.fail(failure, message),
+    }
+    let (answer, time) = phase("part 2", budget, || solver.part2());
+    report.times.part2 = time;
+    match answer {
+        Ok(answer) => report.part2 = Some(answer),
+        Err((failure, message)) => report.fail(failure, message),
+    }
+
+    report
+}
+
+// Runs one phase of a day within `budget`, describing how it failed if it did.
+fn phase<T, F: FnOnce() -> T>(
+    name: &str,
+    budget: Budget,
+    f: F,
+) -> (Result<T, (Failure, String)>, Duration) {
+    timed(|| match catch(|| budget::limit(budget, f)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(exceeded)) => Err((Failure::Budget, format!("{} {}", name, exceeded))),
+        Err(message) => Err((Failure::Panic, format!("{} panicked: {}", name, message))),
+    })
+}
+
+/// Runs every registered day against its file in the `data` directory, `jobs` at a time.
+pub fn run_all(jobs: usize, budget: Budget) -> Vec<Report> {
+    let days: Vec<&Day> = DAYS.iter().collect();
+    run_days(&days, jobs, budget)
+}
+
+/// Runs `days` against their data files on a pool of `jobs` worker threads.
+///
+/// Reports come back in the same order as `days`, however the work was interleaved. Workers pull
+/// days off a shared counter rather than a locked queue, so a worker that dies takes nothing else
+/// down with it; any day it never reported on is marked as failed.
+pub fn run_days(days: &[&Day], jobs: usize, budget: Budget) -> Vec<Report> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, days.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let day = match days.get(i) {
+                    Some(day) => day,
+                    None => break,
+                };
+                let report = catch(|| run_day_from_data(day, budget)).unwrap_or_else(|message| {
+                    let error = format!("worker panicked: {}", message);
+                    Report::failed(day.number, Failure::Panic, error)
+                });
+                if sender.send((i, report)).is_err() {
+                    break;
+                }
+            });
+        }
+    });
+    drop(sender);
+
+    let mut reports: Vec<Option<Report>> = days.iter().map(|_| None).collect();
+    for (i, report) in receiver {
+        reports[i] = Some(report);
+    }
+    reports
+        .into_iter()
+        .zip(days)
+        .map(|(report, day)| {
+            report.unwrap_or_else(|| {
+                let error = "worker exited without a report".to_string();
+                Report::failed(day.number, Failure::Panic, error)
+            })
+        })
+        .collect()
+}
+
+/// Runs `day` against its file in the `data` directory, within `budget`.
+pub fn run_day_from_data(day: &Day, budget: Budget) -> Report {
+    match Source::File(day.data_path()).open() {
+        Ok(mut input) => run_day_within(day, &mut input, budget),
+        Err(e) => Report::failed(day.number, Failure::Input, e.to_string()),
+    }
+}
+
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub(crate) fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_day;
+
+    #[test]
+    fn run_example() {
+        let day = find_day("day1").unwrap();
+        let report = run_day(day, &mut "1721\n979\n366\n299\n675\n1456\n".as_bytes());
+        assert!(report.succeeded());
+        assert_eq!(report.part1, Some(Answer::from(514579usize)));
+        assert_eq!(report.part2, Some(Answer::from(241861950usize)));
+    }
+
+    #[test]
+    fn report_json() {
+        let mut report = Report::new(3);
+        report.part1 = Some(Answer::from(7usize));
+        report.error = Some("part 2 panicked: oops".into());
+        report.times.part1 = Duration::from_micros(5);
+        assert_eq!(
+            report.to_json().to_string(),
+            r#"{"day":3,"part1":7,"part2":null,"error":"part 2 panicked: oops","timing":{"parse_ns":0,"part1_ns":5000,"part2_ns":0}}"#
+        );
+    }
+
+    #[test]
+    fn parse_error_is_reported() {
+        let day = find_day("day1").unwrap();
+        let report = run_day(day, &mut "not a number\n".as_bytes());
+        assert!(!report.succeeded());
+        assert_eq!(report.part1, None);
+        assert_eq!(report.failure, Some(Failure::Input));
+        assert!(report.error.unwrap().starts_with("parse failed"));
+    }
+
+    #[test]
+    fn panic_is_reported() {
+        // There are too few numbers for day 9 to find one that breaks the rule.
+        let day = find_day("day9").unwrap();
+        let report = run_day(day, &mut "1\n2\n3\n".as_bytes());
+        assert!(!report.succeeded());
+        assert_eq!(report.part1, None);
+        assert_eq!(report.failure, Some(Failure::Panic));
+        assert!(report.error.unwrap().starts_with("part 1 panicked"));
+    }
+
+    #[test]
+    fn budget_exceeded_is_reported() {
+        // No time is a multiple of 4 with the next a multiple of 6, so part 2 never finishes.
+        let day = find_day("day13").unwrap();
+        let budget = Budget {
+            steps: Some(1000),
+            ..Budget::default()
+        };
+        let report = run_day_within(day, &mut "10\n4,6\n".as_bytes(), budget);
+        assert_eq!(report.part1, Some(Answer::from(8usize)));
+        assert_eq!(report.failure, Some(Failure::Budget));
+        let error = report.error.unwrap();
+        assert!(
+            error.starts_with("part 2 budget exceeded after 1001 steps"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn run_days_in_order() {
+        let days: Vec<&Day> = DAYS.iter().filter(|day| day.number <= 6).collect();
+        let serial = run_days(&days, 1, Budget::default());
+        let parallel = run_days(&days, 4, Budget::default());
+        let numbers: Vec<usize> = parallel.iter().map(|report| report.day).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6]);
+        for (a, b) in serial.iter().zip(&parallel) {
+            assert_eq!((&a.part1, &a.part2), (&b.part1, &b.part2));
+        }
+    }
+
+    #[test]
+    fn run_no_days() {
+        assert!(run_days(&[], 4, Budget::default()).is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// The module written for a new day. `{number}` is replaced with the day's number.
+///
+/// Both parts start out returning `Answer::unsolved()` rather than panicking, so that `all`,
+/// `serve` and `batch` keep working while the day is in progress.
+const TEMPLATE: &str = r#"use crate::answer::Answer;
+use crate::error::{parse_lines, LineError, Result};
+use crate::solver::Solver;
+
+pub struct Day{number}(pub Vec<String>);
+
+impl Solver for Day{number} {
+    fn parse<IO: std::io::BufRead>(input: IO) -> Result<Self> {
+        let lines = input.lines().collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Day{number}(parse_lines(lines.iter().map(String::as_str), parse_line)?))
+    }
+
+    fn part1(&self) -> Answer {
+        Answer::unsolved()
+    }
+
+    fn part2(&self) -> Answer {
+        Answer::unsolved()
+    }
+}
+
+pub fn parse_line(line: &str) -> std::result::Result<String, LineError> {
+    Ok(line.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Scratch;
+
+    const EXAMPLE: &str = "";
+
+    #[test]
+    fn parse_example() {
+        assert!(Day{number}::parse(EXAMPLE.as_bytes()).is_ok());
+    }
+
+    #[test]
+    #[ignore = "fill in the example answer"]
+    fn part1_example() {
+        let puzzle = Day{number}::parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(puzzle.part1(), "");
+    }
+
+    #[test]
+    #[ignore = "fill in the example answer"]
+    fn part2_example() {
+        let puzzle = Day{number}::parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(puzzle.part2(), "");
+    }
+}
+"#;
+
+/// Adds day `number` to the crate rooted at `root`.
+///
+/// This writes `src/dayN.rs` from a template, declares the module in `src/lib.rs`, adds it to the
+/// registry in `src/solver.rs` and creates an empty `data/dayN.txt`. Nothing is written if the
+/// day already has a module or is already declared. Returns the files that were changed.
+pub fn new_day(root: &Path, number: usize) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&number) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("there is no day {}", number),
+        ));
+    }
+    let module = root.join(format!("src/day{}.rs", number));
+    if module.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", module.display()),
+        ));
+    }
+
+    // Work out every edit before writing anything, so a failure leaves the tree untouched.
+    let lib = root.join("src/lib.rs");
+    let lib_text = insert_in_order(
+        &read(&lib)?,
+        number,
+        &format!("pub mod day{};", number),
+        |line| line.strip_prefix("pub mod day")?.strip_suffix(';'),
+    )
+    .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", lib.display(), e)))?;
+    let solver = root.join("src/solver.rs");
+    let solver_text = edit_block(&read(&solver)?, REGISTRY_START, REGISTRY_END, |block| {
+        insert_in_order(
+            block,
+            number,
+            &format!("    {} => crate::day{}::Day{},", number, number, number),
+            |line| line.trim_start().split(" => crate::day").next(),
+        )
+    })
+    .map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", solver.display(), e),
+        )
+    })?;
+
+    fs::write(&module, TEMPLATE.replace("{number}", &number.to_string()))?;
+    fs::write(&lib, lib_text)?;
+    fs::write(&solver, solver_text)?;
+    let mut changed = vec![module, lib, solver];
+    let data = root.join(format!("data/day{}.txt", number));
+    if !data.exists() {
+        fs::create_dir_all(root.join("data"))?;
+        fs::write(&data, "")?;
+        changed.push(data);
+    }
+    Ok(changed)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("could not read {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// The lines that open and close the registry in `src/solver.rs`.
+const REGISTRY_START: &str = "pub static DAYS: &[Day] = days![";
+const REGISTRY_END: &str = "];";
+
+/// Applies `edit` to the lines of `text` after the one that is `start` and before the next one
+/// that is `end`, leaving the rest of the text as it was.
+fn edit_block<F>(text: &str, start: &str, end: &str, edit: F) -> std::result::Result<String, String>
+where
+    F: FnOnce(&str) -> std::result::Result<String, String>,
+{
+    let mut offset = 0;
+    let mut block_start = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        match block_start {
+            None if trimmed == start => block_start = Some(offset + line.len()),
+            Some(begin) if trimmed == end => {
+                return Ok(format!(
+                    "{}{}{}",
+                    &text[..begin],
+                    edit(&text[begin..offset])?,
+                    &text[offset..]
+                ));
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    Err(format!(
+        "could not find the `{}` ... `{}` block",
+        start, end
+    ))
+}
+
+/// Inserts `line` among the lines of `text` that `day_of` recognizes as belonging to a day,
+/// keeping them in numeric order.
+fn insert_in_order<'a, F>(
+    text: &'a str,
+    number: usize,
+    line: &str,
+    day_of: F,
+) -> std::result::Result<String, String>
+where
+    F: Fn(&'a str) -> Option<&'a str>,
+{
+    let lines: Vec<&str> = text.lines().collect();
+    let days: Vec<(usize, usize)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, day_of(line)?.parse().ok()?)))
+        .collect();
+    if days.iter().any(|&(_, day)| day == number) {
+        return Err(format!("day{} is already registered", number));
+    }
+    let position = match days.iter().find(|&&(_, day)| day > number) {
+        Some(&(i, _)) => i,
+        None => match days.last() {
+            Some(&(i, _)) => i + 1,
+            None => return Err("could not find where days are registered".to_string()),
+        },
+    };
+
+    let mut result: Vec<&str> = lines[..position].to_vec();
+    result.push(line);
+    result.extend(&lines[position..]);
+    Ok(result.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Scratch;
+
+    const LIB: &str = "pub mod day1;\npub mod day3;\n\npub mod solver;\n";
+    const SOLVER: &str = "pub static DAYS: &[Day] = days![
+    1 => crate::day1::Expenses,
+    3 => crate::day3::Map,
+];
+";
+
+    fn scratch_crate(name: &str) -> Scratch {
+        let scratch = Scratch::new(&format!("scaffold-{}", name));
+        fs::create_dir(scratch.join("src")).unwrap();
+        fs::write(scratch.join("src/lib.rs"), LIB).unwrap();
+        fs::write(scratch.join("src/solver.rs"), SOLVER).unwrap();
+        scratch
+    }
+
+    #[test]
+    fn insert_between() {
+        let text = insert_in_order(LIB, 2, "pub mod day2;", |line| {
+            line.strip_prefix("pub mod day")?.strip_suffix(';')
+        })
+        .unwrap();
+        assert_eq!(
+            text,
+            "pub mod day1;\npub mod day2;\npub mod day3;\n\npub mod solver;\n"
+        );
+    }
+
+    #[test]
+    fn scaffold_day() {
+        let scratch = scratch_crate("new");
+        let root = scratch.path();
+        let changed = new_day(root, 17).unwrap();
+        assert_eq!(changed.len(), 4);
+        let module = fs::read_to_string(root.join("src/day17.rs")).unwrap();
+        assert!(module.contains("pub struct Day17(pub Vec<String>);"));
+        assert!(!module.contains("todo!"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod day1;\npub mod day3;\npub mod day17;\n\npub mod solver;\n"
+        );
+        assert!(fs::read_to_string(root.join("src/solver.rs"))
+            .unwrap()
+            .contains("    3 => crate::day3::Map,\n    17 => crate::day17::Day17,\n];"));
+        assert_eq!(fs::read_to_string(root.join("data/day17.txt")).unwrap(), "");
+    }
+
+    #[test]
+    fn scaffold_into_this_crate() {
+        // The real registry, which has registry-shaped lines outside `DAYS` too.
+        let last = crate::solver::DAYS.last().unwrap().name();
+        let number = crate::solver::DAYS.len() + 1;
+        if number > 25 {
+            return;
+        }
+        let scratch = scratch_crate("real");
+        let root = scratch.path();
+        fs::write(root.join("src/lib.rs"), include_str!("lib.rs")).unwrap();
+        fs::write(root.join("src/solver.rs"), include_str!("solver.rs")).unwrap();
+        new_day(root, number).unwrap();
+
+        let solver = fs::read_to_string(root.join("src/solver.rs")).unwrap();
+        let registry = &solver[solver.find(REGISTRY_START).unwrap()..];
+        let registry = &registry[..registry.find(REGISTRY_END).unwrap()];
+        let entry = format!("    {0} => crate::day{0}::Day{0},\n", number);
+        assert!(registry.ends_with(&entry), "{}", registry);
+        assert_eq!(solver.matches(&entry).count(), 1);
+        let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains(&format!("pub mod {};\npub mod day{};\n", last, number)));
+    }
+
+    #[test]
+    fn refuse_existing_day() {
+        let scratch = scratch_crate("existing");
+        let root = scratch.path();
+        fs::write(root.join("src/day3.rs"), "// mine").unwrap();
+        let error = new_day(root, 3).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(
+            fs::read_to_string(root.join("src/day3.rs")).unwrap(),
+            "// mine"
+        );
+
+        // Registered without a module is also refused, and nothing is written.
+        fs::remove_file(root.join("src/day3.rs")).unwrap();
+        assert!(new_day(root, 3).is_err());
+        assert!(!root.join("src/day3.rs").exists());
+    }
+}
@@ -0,0 +1,394 @@
+//! `serve`: the solvers over HTTP, for pasting an input into a browser or `curl`ing it.
+//!
+//! `POST /dayN` with the puzzle input as the body answers with the day's report as JSON, the same
+//! object `--format json` prints. The status says how the run went: 200 if both parts were solved,
+//! 400 if the input could not be parsed, 503 if the solver ran out of time and 500 if it panicked
+//! or crashed. `GET /` serves a page with a form that does the same.
+//!
+//! Solvers only stop early for their budget if they check it, so each solve also has a hard
+//! deadline. With `Config::isolation`, each one runs in a child process that is killed at the
+//! deadline, and whose stack overflow is its own. Otherwise solves run on worker threads, and one
+//! that passes the deadline is answered for but left running. Either way, only so many requests
+//! are handled at once; a worker left running still counts against that until it finishes.
+
+use crate::budget::Budget;
+use crate::http::{HttpError, Request, Response};
+use crate::isolate::{self, Command, Exit};
+use crate::runner::{self, Failure, Report};
+use crate::solver::{find_day, Day, DAYS};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Solving has three phases, each allowed `Config::timeout`, so that is how long the whole solve
+/// may take, times this.
+const PHASES: u32 = 3;
+
+/// Limits on what one request may use, and where solves run.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The largest body accepted, in bytes.
+    pub max_body: usize,
+    /// How long a request has to arrive, and how long each phase of solving it may run.
+    pub timeout: Duration,
+    /// The most requests handled at once. Connections past that are answered with 503 straight
+    /// away.
+    pub max_in_flight: usize,
+    /// Starts a child process that solves its stdin as the day named by its second-to-last
+    /// argument, with the phase timeout in milliseconds last, as `solve-child` does. Without one,
+    /// solves run on worker threads of this process.
+    pub isolation: Option<Command>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_body: 1024 * 1024,
+            timeout: Duration::from_secs(10),
+            max_in_flight: 16,
+            isolation: None,
+        }
+    }
+}
+
+/// Answers connections on `listener` until accepting one fails, each on its own thread, with at
+/// most `config.max_in_flight` at a time.
+pub fn serve(listener: TcpListener, config: Config) -> io::Result<()> {
+    let config = Arc::new(config);
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let slot = match Slot::take(&in_flight, config.max_in_flight) {
+            Some(slot) => Arc::new(slot),
+            None => {
+                // Turned away without reading the request, which is fine for a client told to
+                // come back later.
+                let _ = busy(&stream, config.timeout);
+                continue;
+            }
+        };
+        let config = config.clone();
+        thread::spawn(move || {
+            // There is no one left to tell if the client has gone away.
+            let _ = handle(stream, &config, &slot);
+        });
+    }
+    Ok(())
+}
+
+/// One request's place among those in flight, given up when the last holder drops it.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(in_flight: &Arc<AtomicUsize>, max: usize) -> Option<Slot> {
+        in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                if n < max {
+                    Some(n + 1)
+                } else {
+                    None
+                }
+            })
+            .ok()
+            .map(|_| Slot(in_flight.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn busy(mut stream: &TcpStream, timeout: Duration) -> io::Result<()> {
+    stream.set_write_timeout(Some(timeout))?;
+    HttpError::new(503, "too many requests in flight; try again shortly")
+        .to_response()
+        .with_header("Retry-After", "1")
+        .write_to(&mut stream)?;
+    stream.flush()?;
+    stream.shutdown(Shutdown::Both)
+}
+
+fn handle(stream: TcpStream, config: &Config, slot: &Arc<Slot>) -> io::Result<()> {
+    stream.set_write_timeout(Some(config.timeout))?;
+    let mut reader = BufReader::new(Deadline {
+        stream: &stream,
+        end: Instant::now() + config.timeout,
+    });
+    let response = match Request::read(&mut reader, config.max_body) {
+        Ok(request) => respond(&request, config, slot),
+        Err(e) => e.to_response(),
+    };
+    response.write_to(&mut &stream)?;
+
+    // Closing with part of a rejected request still unread would reset the connection, and the
+    // client might never see the response. Let it finish sending first, up to a point.
+    stream.shutdown(Shutdown::Write)?;
+    io::copy(&mut reader.take(config.max_body as u64), &mut io::sink())?;
+    Ok(())
+}
+
+// Answers one request. A worker thread left running past the deadline holds on to `slot`.
+fn respond(request: &Request, config: &Config, slot: &Arc<Slot>) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => Response::new(200, "text/html; charset=utf-8", page()),
+        (method, path) => {
+            let day = match find_day(path.trim_start_matches('/')) {
+                Some(day) => day,
+                None => return HttpError::new(404, format!("no such day: {}", path)).to_response(),
+            };
+            if method != "POST" {
+                return HttpError::new(405, "post the puzzle input to this path")
+                    .to_response()
+                    .with_header("Allow", "POST");
+            }
+            solve(day, request.body.clone(), config, slot.clone())
+        }
+    }
+}
+
+// Solves `input` as `day` in a child process or on a worker thread, stopping waiting at the hard
+// deadline.
+fn solve(day: &'static Day, input: Vec<u8>, config: &Config, slot: Arc<Slot>) -> Response {
+    let deadline = config.timeout * PHASES;
+    let failed = |failure, error| report_response(&Report::failed(day.number, failure, error));
+    let stopped = || {
+        let error = format!("stopped after {:.2?}", deadline);
+        failed(Failure::Budget, error)
+    };
+    if let Some(command) = &config.isolation {
+        let args = [day.name(), config.timeout.as_millis().to_string()];
+        return match command.run(&[&args[0], &args[1]], &input, deadline) {
+            Ok(Exit::Finished(output)) => decode(&output).unwrap_or_else(|| {
+                let error = "the solver's process gave no report".to_string();
+                failed(Failure::Panic, error)
+            }),
+            Ok(Exit::Panicked(message)) => failed(Failure::Panic, format!("panicked: {}", message)),
+            Ok(Exit::Crashed(how)) => failed(Failure::Panic, format!("crashed: {}", how)),
+            Ok(Exit::TimedOut) => stopped(),
+            Err(e) => failed(Failure::Panic, format!("could not start a solver: {}", e)),
+        };
+    }
+
+    let budget = phase_budget(config.timeout);
+    let receiver = isolate::spawn(move || {
+        let _slot = slot;
+        runner::run_day_within(day, &mut &input[..], budget)
+    });
+    match receiver.map(|receiver| receiver.recv_timeout(deadline)) {
+        Ok(Ok(Ok(report))) => report_response(&report),
+        Ok(Ok(Err(message))) => failed(Failure::Panic, format!("panicked: {}", message)),
+        Ok(Err(RecvTimeoutError::Timeout)) => stopped(),
+        Ok(Err(RecvTimeoutError::Disconnected)) => {
+            failed(Failure::Panic, "the worker died".to_string())
+        }
+        Err(e) => failed(Failure::Panic, format!("could not start a worker: {}", e)),
+    }
+}
+
+fn phase_budget(timeout: Duration) -> Budget {
+    Budget {
+        time: Some(timeout),
+        steps: None,
+    }
+}
+
+fn report_response(report: &Report) -> Response {
+    let status = match report.failure {
+        None => 200,
+        Some(Failure::Input) => 400,
+        Some(Failure::Budget) => 503,
+        Some(Failure::Panic) => 500,
+    };
+    Response::json(status, &report.to_json())
+}
+
+/// The `solve-child` end of `Config::isolation`: solves `input` as the day called `name` on a
+/// thread of its own, with `timeout` for each phase. The result is the response's status on a
+/// line of its own, then its body.
+pub fn child(name: &str, timeout: Duration, input: Vec<u8>) -> Result<Vec<u8>, String> {
+    let day = find_day(name).ok_or_else(|| format!("no such day: {}", name))?;
+    let response = isolate::on_thread(move || {
+        report_response(&runner::run_day_within(
+            day,
+            &mut &input[..],
+            phase_budget(timeout),
+        ))
+    })?;
+    let mut output = format!("{}\n", response.status).into_bytes();
+    output.extend(response.body);
+    Ok(output)
+}
+
+// Reads what `child` wrote back into a response.
+fn decode(output: &[u8]) -> Option<Response> {
+    let newline = output.iter().position(|&b| b == b'\n')?;
+    let status = std::str::from_utf8(&output[..newline]).ok()?.parse().ok()?;
+    Some(Response::new(
+        status,
+        "application/json",
+        &output[newline + 1..],
+    ))
+}
+
+// Reads from a stream, giving up once the deadline passes however slowly the bytes trickle in.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    end: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.end.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn page() -> String {
+    let options: String = DAYS
+        .iter()
+        .map(|day| format!("<option>{}</option>", day.name()))
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<title>Advent of Code 2020</title>
+<select id="day">{}</select>
+<button onclick="solve()">Solve</button>
+<p><textarea id="input" rows="20" cols="80" placeholder="Puzzle input"></textarea>
+<pre id="output"></pre>
+<script>
+async function solve() {{
+  const day = document.getElementById("day").value;
+  const input = document.getElementById("input").value;
+  const response = await fetch("/" + day, {{method: "POST", body: input}});
+  document.getElementById("output").textContent = response.status + "\n" + await response.text();
+}}
+</script>
+"#,
+        options
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use std::net::SocketAddr;
+
+    fn start(config: Config) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, config));
+        address
+    }
+
+    fn get(address: SocketAddr) -> String {
+        send(address, "GET / HTTP/1.1\r\n\r\n")
+    }
+
+    fn send(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn post(address: SocketAddr, path: &str, body: &str) -> String {
+        send(
+            address,
+            &format!(
+                "POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                path,
+                body.len(),
+                body
+            ),
+        )
+    }
+
+    #[test]
+    fn solve_over_http() {
+        let address = start(Config::default());
+        let response = post(address, "/day1", "1721\n979\n366\n299\n675\n1456\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains(r#""part1":514579,"part2":241861950,"error":null"#));
+
+        let response = post(address, "/day1", "1721\nabc\n");
+        assert!(response.starts_with("HTTP/1.1 400 "), "{}", response);
+        assert!(response.contains("parse failed: day1 line 2, column 1"));
+
+        assert!(post(address, "/day99", "").starts_with("HTTP/1.1 404 "));
+        let response = send(address, "GET /day1 HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 "));
+        assert!(response.contains("Allow: POST\r\n"));
+        assert!(send(address, "GET / HTTP/1.1\r\n\r\n").contains("<option>day16</option>"));
+    }
+
+    #[test]
+    fn limits() {
+        let address = start(Config {
+            max_body: 10,
+            timeout: Duration::from_millis(200),
+            ..Config::default()
+        });
+        let response = post(address, "/day1", "1721\n979\n366\n");
+        assert!(response.starts_with("HTTP/1.1 413 "), "{}", response);
+
+        // A client that never finishes its request is cut off.
+        let response = send(address, "POST /day1 HTTP/1.1\r\nContent-Length: 5\r\n\r\n1");
+        assert!(response.starts_with("HTTP/1.1 408 "), "{}", response);
+
+        // Neither is a solver that never finishes.
+        let response = post(address, "/day13", "10\n4,6\n");
+        assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+        assert!(response.contains("budget exceeded"));
+    }
+
+    #[test]
+    fn survives_bad_requests() {
+        let address = start(Config::default());
+        // Once took the whole server down by overflowing the stack.
+        let response = post(address, "/day12", "R900000000\nF10\n");
+        assert!(response.starts_with("HTTP/1.1 200 "), "{}", response);
+        assert!(response.contains(r#""part1":10,"#), "{}", response);
+        assert!(get(address).starts_with("HTTP/1.1 200 "));
+    }
+
+    #[test]
+    fn hard_deadline_and_capacity() {
+        let address = start(Config {
+            timeout: Duration::from_millis(50),
+            max_in_flight: 1,
+            ..Config::default()
+        });
+        // Day 15 never checks its budget, so only the deadline stops the wait for it.
+        let started = Instant::now();
+        let response = post(address, "/day15", "0,3,6\n");
+        assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+        assert!(response.contains("stopped after"), "{}", response);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // Its worker is still running, so there is no room for another request until it is done.
+        let response = get(address);
+        assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+        assert!(response.contains("Retry-After: 1\r\n"), "{}", response);
+    }
+
+    #[test]
+    fn decode_child_output() {
+        let response = decode(b"400\n{\"day\":1}\n").unwrap();
+        assert_eq!(response.status, 400);
+        assert_eq!(response.body, b"{\"day\":1}\n");
+        assert!(decode(b"").is_none());
+        assert!(decode(b"abc\n{}").is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::answer::Answer;
+use crate::error::Result;
+use std::io::BufRead;
+
+/// A solution to one day's puzzle.
+///
+/// Implementors are the parsed form of that day's input. `parse` builds one from the raw puzzle
+/// input and `part1` and `part2` compute the answers from it.
+pub trait Solver {
+    fn parse<IO: BufRead>(input: IO) -> Result<Self>
+    where
+        Self: Sized;
+
+    fn part1(&self) -> Answer;
+    fn part2(&self) -> Answer;
+}
+
+/// An entry in the registry of days that have a solver.
+pub struct Day {
+    pub number: usize,
+    /// Bumped whenever a change to the solver could change its answers, which invalidates any
+    /// answers cached for the day. Written as `N => Solver: version V` in `DAYS`, and 1 if left out.
+    pub version: u32,
+    parse: fn(&mut dyn BufRead) -> Result<Box<dyn Solver>>,
+}
+
+impl Day {
+    pub fn name(&self) -> String {
+        format!("day{}", self.number)
+    }
+
+    /// The checked-in puzzle input for this day.
+    pub fn data_path(&self) -> std::path::PathBuf {
+        format!("data/{}.txt", self.name()).into()
+    }
+
+    pub fn parse(&self, input: &mut dyn BufRead) -> Result<Box<dyn Solver>> {
+        (self.parse)(input).map_err(|e| e.for_day(self.number))
+    }
+}
+
+fn parse_boxed<S: Solver + 'static>(input: &mut dyn BufRead) -> Result<Box<dyn Solver>> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+macro_rules! days {
+    ($($number:expr => $solver:ty $(: version $version:expr)?),* $(,)?) => {
+        &[$(Day {
+            number: $number,
+            version: version!($($version)?),
+            parse: parse_boxed::<$solver>,
+        }),*]
+    };
+}
+
+macro_rules! version {
+    () => {
+        1
+    };
+    ($version:expr) => {
+        $version
+    };
+}
+
+/// All days with a solver, in order.
+pub static DAYS: &[Day] = days![
+    1 => crate::day1::Expenses,
+    2 => crate::day2::PasswordList,
+    3 => crate::day3::Map,
+    4 => crate::day4::Passports,
+    5 => crate::day5::Seats,
+    6 => crate::day6::Groups,
+    7 => crate::day7::Rules,
+    8 => crate::day8::Machine,
+    9 => crate::day9::Xmas,
+    10 => crate::day10::Adapters,
+    11 => crate::day11::FloorPlan,
+    12 => crate::day12::Directions,
+    13 => crate::day13::Schedule,
+    14 => crate::day14::Program,
+    15 => crate::day15::StartingNumbers,
+    16 => crate::day16::Problem,
+];
+
+/// Looks up a day by name, e.g. `day7`.
+pub fn find_day(name: &str) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.name() == name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn days_are_in_order() {
+        for (i, day) in DAYS.iter().enumerate() {
+            assert_eq!(day.number, i + 1);
+        }
+    }
+
+    #[test]
+    fn versions() {
+        use crate::day1::Expenses;
+        use crate::day2::PasswordList;
+        // Spelled differently from the lines in `DAYS`, which `new-day` looks for.
+        let days: &[Day] = days![
+            1 => Expenses,
+            2 => PasswordList: version 3,
+        ];
+        assert_eq!(days[0].version, 1);
+        assert_eq!(days[1].version, 3);
+    }
+
+    #[test]
+    fn find_by_name() {
+        assert_eq!(find_day("day7").map(|day| day.number), Some(7));
+        assert!(find_day("day42").is_none());
+    }
+}
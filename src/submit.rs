@@ -0,0 +1,597 @@
+//! `submit`: posts an answer to the puzzle site and records what it said.
+//!
+//! Every attempt is appended to a history file, and the history is checked before anything is
+//! sent. An answer that has already been judged wrong is refused, and so is one that an earlier
+//! "too high" or "too low" rules out. So is any attempt made before the cooldown since the last
+//! one has passed, since the site makes you wait after a wrong answer anyway.
+
+use crate::answer::Answer;
+use crate::error::{parse_number, LineError, ParseError};
+use crate::fetch::{read_token, session_url, DEFAULT_SESSION_FILE};
+use crate::http;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The file attempts are recorded in by default.
+pub const DEFAULT_HISTORY_FILE: &str = "submissions.txt";
+
+/// How long to wait between attempts by default.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+pub struct Config {
+    /// The year's page on the site, or a local proxy for it. Answers are posted below it.
+    pub base_url: String,
+    pub session_file: PathBuf,
+    /// Send the session token to a host other than this machine, in the clear.
+    pub insecure: bool,
+    pub history_file: PathBuf,
+    /// The least time between two attempts. The site may ask for longer.
+    pub cooldown: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            base_url: String::new(),
+            session_file: DEFAULT_SESSION_FILE.into(),
+            insecure: false,
+            history_file: DEFAULT_HISTORY_FILE.into(),
+            cooldown: DEFAULT_COOLDOWN,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// What the site said about an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying which way.
+    Wrong,
+    /// The answer came too soon after the last one and was not looked at. The site says how much
+    /// longer to wait, if it can be made out.
+    Wait(Option<Duration>),
+    /// The part has already been solved, or cannot be attempted yet.
+    WrongLevel,
+    /// The answer was sent, but no verdict could be made out of what came back, if anything did.
+    Unknown,
+}
+
+impl Verdict {
+    /// Finds the verdict in the page the site answers a submission with.
+    pub fn parse(page: &str) -> Option<Verdict> {
+        if page.contains("That's the right answer") {
+            Some(Verdict::Correct)
+        } else if page.contains("That's not the right answer") {
+            Some(if page.contains("too high") {
+                Verdict::TooHigh
+            } else if page.contains("too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            })
+        } else if page.contains("You gave an answer too recently") {
+            Some(Verdict::Wait(parse_wait(page)))
+        } else if page.contains("You don't seem to be solving the right level") {
+            Some(Verdict::WrongLevel)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the verdict says the answer is wrong, as opposed to not judging it at all.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+
+    // The verdict as written in the history file.
+    fn name(&self) -> String {
+        match self {
+            Verdict::Correct => "correct".to_string(),
+            Verdict::TooHigh => "too-high".to_string(),
+            Verdict::TooLow => "too-low".to_string(),
+            Verdict::Wrong => "wrong".to_string(),
+            Verdict::Wait(None) => "wait".to_string(),
+            Verdict::Wait(Some(wait)) => format!("wait={}", wait.as_secs()),
+            Verdict::WrongLevel => "wrong-level".to_string(),
+            Verdict::Unknown => "unknown".to_string(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Verdict> {
+        match name {
+            "correct" => Some(Verdict::Correct),
+            "too-high" => Some(Verdict::TooHigh),
+            "too-low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Wrong),
+            "wait" => Some(Verdict::Wait(None)),
+            "wrong-level" => Some(Verdict::WrongLevel),
+            "unknown" => Some(Verdict::Unknown),
+            _ => {
+                let seconds = name.strip_prefix("wait=")?.parse().ok()?;
+                Some(Verdict::Wait(Some(Duration::from_secs(seconds))))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Wait(None) => write!(f, "too soon"),
+            Verdict::Wait(Some(wait)) => write!(f, "too soon; wait {}", format_wait(*wait)),
+            Verdict::WrongLevel => write!(f, "not the level being solved"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// Reads the time left out of "You have 4m 32s left to wait".
+fn parse_wait(page: &str) -> Option<Duration> {
+    let start = page.find("You have ")? + "You have ".len();
+    let end = start + page[start..].find(" left to wait")?;
+    let mut seconds = 0;
+    for amount in page[start..end].split_whitespace() {
+        let unit = match amount.chars().last()? {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds += unit * amount[..amount.len() - 1].parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+fn format_wait(wait: Duration) -> String {
+    let seconds = wait.as_secs();
+    if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// One answer sent for one part, and what came of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attempt {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Answer,
+    pub verdict: Verdict,
+    /// When it was sent, to the second.
+    pub at: SystemTime,
+}
+
+impl fmt::Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = self.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.day,
+            self.part,
+            at.as_secs(),
+            self.verdict.name(),
+            self.answer
+        )
+    }
+}
+
+/// Why an answer was not sent.
+#[derive(Debug, PartialEq)]
+pub enum Refusal {
+    /// The part has already been solved with this answer.
+    Solved(Answer),
+    /// This earlier attempt shows the answer is wrong, either because it is the same answer or
+    /// because it was too high or too low and the new answer is further off still.
+    KnownWrong(Answer, Attempt),
+    /// The last attempt was too recent. This is how much longer to wait.
+    Cooldown(Duration),
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::Solved(answer) => write!(f, "already solved; the answer is {}", answer),
+            Refusal::KnownWrong(answer, attempt) if *answer == attempt.answer => {
+                write!(f, "{} was already {}", answer, attempt.verdict)
+            }
+            Refusal::KnownWrong(answer, attempt) => write!(
+                f,
+                "{} cannot be right, since {} was {}",
+                answer, attempt.answer, attempt.verdict
+            ),
+            Refusal::Cooldown(wait) => write!(
+                f,
+                "the last attempt was too recent; wait {}",
+                format_wait(*wait)
+            ),
+        }
+    }
+}
+
+/// Every attempt made so far, oldest first.
+///
+/// The file format has one attempt per line, as `day part time verdict answer`, where `time` is
+/// in seconds since the Unix epoch. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default, PartialEq)]
+pub struct History(pub Vec<Attempt>);
+
+impl History {
+    pub fn parse(text: &str) -> Result<History, ParseError> {
+        let mut history = History::default();
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            history
+                .0
+                .push(parse_attempt(line).map_err(|e| e.at(i + 1, line))?);
+        }
+        Ok(history)
+    }
+
+    /// Loads the history at `path`, treating a missing file as no attempts.
+    pub fn load(path: &Path) -> crate::error::Result<History> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(History::parse(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(History::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Adds `attempt` to the end of the file at `path`, creating it if need be.
+    pub fn append(path: &Path, attempt: &Attempt) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "# day part time verdict answer")?;
+        }
+        writeln!(file, "{}", attempt)
+    }
+
+    /// Decides whether sending `answer` for `day` and `part` at `now` could tell us anything.
+    pub fn check(
+        &self,
+        day: usize,
+        part: usize,
+        answer: &Answer,
+        now: SystemTime,
+        cooldown: Duration,
+    ) -> Result<(), Refusal> {
+        let attempts = self.0.iter().filter(|a| a.day == day && a.part == part);
+        for attempt in attempts {
+            if attempt.verdict == Verdict::Correct {
+                return Err(Refusal::Solved(attempt.answer.clone()));
+            }
+            let ruled_out = match (&attempt.verdict, answer.as_integer()) {
+                (verdict, _) if verdict.is_wrong() && attempt.answer == *answer => true,
+                (Verdict::TooHigh, Some(n)) => attempt.answer.as_integer().is_some_and(|h| n >= h),
+                (Verdict::TooLow, Some(n)) => attempt.answer.as_integer().is_some_and(|l| n <= l),
+                _ => false,
+            };
+            if ruled_out {
+                return Err(Refusal::KnownWrong(answer.clone(), attempt.clone()));
+            }
+        }
+
+        if let Some(last) = self.0.last() {
+            let wait = match last.verdict {
+                Verdict::Wait(Some(wait)) => wait.max(cooldown),
+                _ => cooldown,
+            };
+            let left = (last.at + wait)
+                .duration_since(now)
+                .unwrap_or(Duration::ZERO);
+            if !left.is_zero() {
+                return Err(Refusal::Cooldown(left));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_attempt(line: &str) -> Result<Attempt, LineError> {
+    // The answer comes last and takes the rest of the line.
+    let mut fields = line.trim().splitn(5, ' ');
+    match (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    ) {
+        (Some(day), Some(part_text), Some(at), Some(verdict), Some(answer)) => {
+            let day = parse_number(line, day)?;
+            let part = parse_number(line, part_text)?;
+            if part != 1 && part != 2 {
+                return Err(LineError::within(line, part_text, "part must be 1 or 2"));
+            }
+            let at = UNIX_EPOCH + Duration::from_secs(parse_number(line, at)?);
+            let verdict = Verdict::from_name(verdict)
+                .ok_or_else(|| LineError::within(line, verdict, "unknown verdict"))?;
+            Ok(Attempt {
+                day,
+                part,
+                answer: Answer::parse(answer),
+                verdict,
+                at,
+            })
+        }
+        _ => Err(LineError::whole(
+            line,
+            "expected `day part time verdict answer`",
+        )),
+    }
+}
+
+/// Why `submit` did not come back with a verdict.
+#[derive(Debug)]
+pub enum Error {
+    /// The history ruled the answer out, so it was not sent.
+    Refused(Refusal),
+    /// Reading the history or talking to the site failed.
+    Io(io::Error),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Refused(refusal) => write!(f, "not submitted: {}", refusal),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Sends `answer` for `day` and `part`, unless the history rules it out, and records the verdict.
+///
+/// Once the answer has been sent the attempt is recorded even if no verdict comes back, as
+/// `Verdict::Unknown`, so that the cooldown still applies.
+pub fn submit(day: usize, part: usize, answer: &Answer, config: &Config) -> Result<Verdict, Error> {
+    let history = History::load(&config.history_file).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", config.history_file.display(), e),
+        )
+    })?;
+    let now = SystemTime::now();
+    history
+        .check(day, part, answer, now, config.cooldown)
+        .map_err(Error::Refused)?;
+
+    let url = session_url(&config.base_url, config.insecure)?;
+    let token = read_token(&config.session_file)?;
+    let cookie = format!("session={}", token);
+    let answer_path = format!("/day/{}/answer", day);
+    let body = format!("level={}&answer={}", part, form_encode(&answer.to_string()));
+    let stream = http::connect(&url, config.timeout)?;
+    let verdict = http::exchange(
+        stream,
+        &url,
+        "POST",
+        &answer_path,
+        &[
+            ("Cookie", &cookie),
+            ("Content-Type", "application/x-www-form-urlencoded"),
+        ],
+        body.as_bytes(),
+    )
+    .and_then(|response| http::expect_ok(&url, &answer_path, response))
+    .and_then(|response| {
+        Verdict::parse(&response.text()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}{} answered with no verdict", url, answer_path),
+            )
+        })
+    });
+
+    let attempt = Attempt {
+        day,
+        part,
+        answer: answer.clone(),
+        verdict: verdict.as_ref().cloned().unwrap_or(Verdict::Unknown),
+        at: now,
+    };
+    History::append(&config.history_file, &attempt)?;
+    Ok(verdict?)
+}
+
+// Escapes everything but letters, digits and `-._~` for a form field.
+fn form_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn attempt(part: usize, answer: &str, verdict: Verdict, seconds: u64) -> Attempt {
+        Attempt {
+            day: 1,
+            part,
+            answer: Answer::parse(answer),
+            verdict,
+            at: at(seconds),
+        }
+    }
+
+    #[test]
+    fn verdicts() {
+        assert_eq!(
+            Verdict::parse("<p>That's the right answer!  You are one gold star closer.</p>"),
+            Some(Verdict::Correct)
+        );
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer; your answer is too high.</p>"),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer; your answer is too low.</p>"),
+            Some(Verdict::TooLow)
+        );
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer.  If you're stuck...</p>"),
+            Some(Verdict::Wrong)
+        );
+        assert_eq!(
+            Verdict::parse("You gave an answer too recently. You have 4m 32s left to wait."),
+            Some(Verdict::Wait(Some(Duration::from_secs(272))))
+        );
+        assert_eq!(
+            Verdict::parse("You gave an answer too recently."),
+            Some(Verdict::Wait(None))
+        );
+        assert_eq!(
+            Verdict::parse("You don't seem to be solving the right level."),
+            Some(Verdict::WrongLevel)
+        );
+        assert_eq!(Verdict::parse("<html>Log in</html>"), None);
+    }
+
+    #[test]
+    fn history_file() {
+        let text = "# day part time verdict answer
+1 1 100 too-high 900
+1 1 200 wait=45 800
+
+1 1 300 correct 514579
+10 2 400 wrong hello world
+2 1 500 unknown 7
+";
+        let history = History::parse(text).unwrap();
+        assert_eq!(history.0.len(), 5);
+        assert_eq!(
+            history.0[1],
+            attempt(1, "800", Verdict::Wait(Some(Duration::from_secs(45))), 200)
+        );
+        assert_eq!(history.0[3].answer, "hello world");
+        let lines: Vec<String> = history.0.iter().map(Attempt::to_string).collect();
+        assert_eq!(
+            lines[1..],
+            [
+                "1 1 200 wait=45 800",
+                "1 1 300 correct 514579",
+                "10 2 400 wrong hello world",
+                "2 1 500 unknown 7"
+            ]
+        );
+
+        let error = History::parse("1 1 100 maybe 5\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
+        let error = History::parse("1 1 100\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+    }
+
+    #[test]
+    fn refuses_known_wrong_answers() {
+        let history = History(vec![
+            attempt(1, "900", Verdict::TooHigh, 100),
+            attempt(1, "100", Verdict::TooLow, 200),
+            attempt(1, "500", Verdict::Wrong, 300),
+        ]);
+        let check =
+            |answer: &str| history.check(1, 1, &Answer::parse(answer), at(1000), DEFAULT_COOLDOWN);
+        assert_eq!(check("499"), Ok(()));
+        assert_eq!(
+            check("500"),
+            Err(Refusal::KnownWrong(
+                Answer::parse("500"),
+                history.0[2].clone()
+            ))
+        );
+        assert_eq!(
+            check("950"),
+            Err(Refusal::KnownWrong(
+                Answer::parse("950"),
+                history.0[0].clone()
+            ))
+        );
+        assert_eq!(
+            check("100").unwrap_err().to_string(),
+            "100 was already too low"
+        );
+        assert_eq!(
+            check("7").unwrap_err().to_string(),
+            "7 cannot be right, since 100 was too low"
+        );
+        // Text answers are only ruled out by the same text.
+        assert_eq!(check("abc"), Ok(()));
+        // Other parts are unaffected.
+        assert_eq!(
+            history.check(1, 2, &Answer::parse("500"), at(1000), DEFAULT_COOLDOWN),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn refuses_solved_parts() {
+        let history = History(vec![attempt(1, "514579", Verdict::Correct, 100)]);
+        assert_eq!(
+            history.check(1, 1, &Answer::parse("514579"), at(1000), DEFAULT_COOLDOWN),
+            Err(Refusal::Solved(Answer::parse("514579")))
+        );
+    }
+
+    #[test]
+    fn cooldown() {
+        let mut history = History(vec![attempt(2, "5", Verdict::Wrong, 100)]);
+        let check = |history: &History, seconds| {
+            history.check(1, 1, &Answer::parse("6"), at(seconds), DEFAULT_COOLDOWN)
+        };
+        assert_eq!(
+            check(&history, 130),
+            Err(Refusal::Cooldown(Duration::from_secs(30)))
+        );
+        assert_eq!(check(&history, 160), Ok(()));
+
+        // The site may ask for longer.
+        history.0.push(attempt(
+            1,
+            "6",
+            Verdict::Wait(Some(Duration::from_secs(272))),
+            200,
+        ));
+        assert_eq!(
+            check(&history, 300).unwrap_err().to_string(),
+            "the last attempt was too recent; wait 2m 52s"
+        );
+        assert_eq!(check(&history, 472), Ok(()));
+    }
+
+    #[test]
+    fn form_encoding() {
+        assert_eq!(form_encode("514579"), "514579");
+        assert_eq!(form_encode("a b&c=d,é"), "a%20b%26c%3Dd%2C%C3%A9");
+    }
+}
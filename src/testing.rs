@@ -0,0 +1,62 @@
+//! Fixtures shared by the unit tests and the tests in `tests/`.
+
+use crate::http::{Request, Response, Url};
+use std::fs;
+use std::io::BufReader;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// An empty scratch directory, removed when dropped.
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+    /// Makes the directory for `name`, which should be unique among the tests. The process id is
+    /// added, so test runs do not share it either.
+    pub fn new(name: &str) -> Scratch {
+        let dir = std::env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A stand-in server, answering each connection with the next of `responses`.
+///
+/// It stops after the last response. Every request it reads is sent to the returned receiver.
+pub fn stand_in(responses: Vec<Response>) -> (Url, Receiver<Request>) {
+    stand_in_on("127.0.0.1:0", responses)
+}
+
+/// `stand_in`, listening on `address`.
+pub fn stand_in_on(address: &str, responses: Vec<Response>) -> (Url, Receiver<Request>) {
+    let listener = TcpListener::bind(address).unwrap();
+    let url = Url::parse(&format!("http://{}/base", listener.local_addr().unwrap())).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for response in responses {
+            let stream = listener.accept().unwrap().0;
+            let request = Request::read(&mut BufReader::new(&stream), 1 << 20).unwrap();
+            response.write_to(&mut &stream).unwrap();
+            if sender.send(request).is_err() {
+                break;
+            }
+        }
+    });
+    (url, receiver)
+}
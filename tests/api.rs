@@ -0,0 +1,58 @@
+use adventofcode2020::error::Error;
+use adventofcode2020::solver::{find_day, Solver};
+use adventofcode2020::{day13, day14, day8, day9};
+
+#[test]
+fn schedule_alignment() {
+    let schedule = day13::Schedule::parse(&b"939\n7,13,x,x,59,x,31,19\n"[..]).unwrap();
+    assert_eq!(schedule.start, 939);
+    assert_eq!(schedule.earliest_bus(), (59, 5));
+    assert_eq!(schedule.earliest_alignment(), 1068781);
+}
+
+#[test]
+fn schedule_parse_error() {
+    match day13::Schedule::parse(&b"939\n7,0,x\n"[..]) {
+        Err(Error::Parse(e)) => assert_eq!((e.line, e.column), (2, 3)),
+        _ => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn xmas_invalid_number() {
+    let numbers = day9::read_input(
+        "35 20 15 25 47 40 62 55 65 95 102 117 150 182 127 219 299 277 309 576".split_whitespace(),
+    )
+    .unwrap();
+    assert_eq!(day9::find_invalid(&numbers, 5), Some(127));
+    assert_eq!(day9::find_segment(&numbers, 127), &[15, 25, 47, 40]);
+}
+
+#[test]
+fn machine_trace() {
+    let program = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+    let mut machine = day8::parse_program(program.lines()).unwrap();
+    assert_eq!(machine.trace(), 5);
+    assert_eq!(day8::fix_loop(&mut machine), 8);
+}
+
+#[test]
+fn mask_instruction() {
+    let mask = day14::parse_instruction("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X").unwrap();
+    assert_eq!(
+        mask,
+        day14::Instruction::Mask("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".into())
+    );
+    let mask = day14::parse_mask("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X");
+    assert_eq!(day14::apply_mask(mask, 11), 73);
+}
+
+#[test]
+fn registry() {
+    let day = find_day("day1").unwrap();
+    let solver = day
+        .parse(&mut &b"1721\n979\n366\n299\n675\n1456\n"[..])
+        .unwrap();
+    assert_eq!(solver.part1(), "514579");
+    assert_eq!(solver.part2(), "241861950");
+}
@@ -0,0 +1,125 @@
+//! Runs each day's checked-in input through its solver and compares against the known answers in
+//! `answers.txt`, the same file `verify` checks against.
+//!
+//! A day whose data file is missing is skipped, so the tests still pass in a checkout without the
+//! puzzle inputs. Every other part must have a known answer or be listed in `KNOWN_FAILURES`.
+
+use adventofcode2020::answer::Answer;
+use adventofcode2020::answers::{self, Answers};
+use adventofcode2020::solver::Solver;
+use adventofcode2020::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Parts that fail on the checked-in input, and so have no known answer. Each is checked to still
+/// fail, so that fixing one makes its test ask for the answer to be recorded.
+const KNOWN_FAILURES: &[(&str, usize)] = &[
+    // Part 2 keeps tickets with any valid value rather than only those whose values are all valid,
+    // and then finds no field that fits.
+    ("day16", 2),
+];
+
+fn read(day: &str) -> Option<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join(format!("{}.txt", day));
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("skipping {}: {} not found", day, path.display());
+            None
+        }
+        Err(e) => panic!("reading {}: {}", path.display(), e),
+    }
+}
+
+fn expected(day: &str, part: usize) -> Option<Answer> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(answers::DEFAULT_PATH);
+    let answers = Answers::load(path.to_str().unwrap())
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    let number = day["day".len()..].parse().unwrap();
+    let answer = answers.get(number, part).cloned();
+    let known_failure = KNOWN_FAILURES.contains(&(day, part));
+    match (&answer, known_failure) {
+        (None, false) => panic!(
+            "{} part {}: no answer in {}; record one with `verify --record {}`",
+            day,
+            part,
+            path.display(),
+            day
+        ),
+        (Some(_), true) => panic!(
+            "{} part {} has an answer in {} but is listed in KNOWN_FAILURES",
+            day,
+            part,
+            path.display()
+        ),
+        _ => answer,
+    }
+}
+
+/// Checks that `part` of `day`, a known failure, still panics.
+fn still_fails(day: &str, part: usize, solve: impl FnOnce() -> Answer) {
+    if let Ok(answer) = panic::catch_unwind(AssertUnwindSafe(solve)) {
+        panic!(
+            "{} part {} is listed in KNOWN_FAILURES but now answers {}; \
+             record it with `verify --record {}` and take it off the list",
+            day, part, answer, day
+        );
+    }
+}
+
+/// Checks one day's answers.
+fn check<S: Solver>(day: &str) {
+    let text = match read(day) {
+        Some(text) => text,
+        None => return,
+    };
+    let (part1, part2) = (expected(day, 1), expected(day, 2));
+    let solver = S::parse(text.as_bytes()).unwrap_or_else(|e| panic!("{}: {}", day, e));
+    match &part1 {
+        Some(part1) => assert_eq!(&solver.part1(), part1, "{} part 1", day),
+        None => still_fails(day, 1, || solver.part1()),
+    }
+    match &part2 {
+        Some(part2) => assert_eq!(&solver.part2(), part2, "{} part 2", day),
+        None => still_fails(day, 2, || solver.part2()),
+    }
+
+    // The same input with Windows line endings should read the same.
+    let crlf = text.replace('\n', "\r\n");
+    let solver = S::parse(crlf.as_bytes()).unwrap_or_else(|e| panic!("{} (CRLF): {}", day, e));
+    if let Some(part1) = &part1 {
+        assert_eq!(&solver.part1(), part1, "{} part 1 (CRLF)", day);
+    }
+}
+
+macro_rules! golden {
+    ($($day:ident: $solver:ty;)*) => {
+        $(
+            #[test]
+            fn $day() {
+                check::<$solver>(stringify!($day));
+            }
+        )*
+    };
+}
+
+golden! {
+    day1: day1::Expenses;
+    day2: day2::PasswordList;
+    day3: day3::Map;
+    day4: day4::Passports;
+    day5: day5::Seats;
+    day6: day6::Groups;
+    day7: day7::Rules;
+    day8: day8::Machine;
+    day9: day9::Xmas;
+    day10: day10::Adapters;
+    day11: day11::FloorPlan;
+    day12: day12::Directions;
+    day13: day13::Schedule;
+    day14: day14::Program;
+    day15: day15::StartingNumbers;
+    day16: day16::Problem;
+}
@@ -0,0 +1,103 @@
+//! The server with each solve in a child process of the real executable, as `serve` runs it.
+
+use adventofcode2020::isolate::Command;
+use adventofcode2020::server::{self, Config};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn start(config: Config) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let config = Config {
+        isolation: Some(Command::new(
+            env!("CARGO_BIN_EXE_adventofcode2020"),
+            &["solve-child"],
+        )),
+        ..config
+    };
+    thread::spawn(move || server::serve(listener, config));
+    address
+}
+
+fn send(address: SocketAddr, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(request).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+fn post(address: SocketAddr, path: &str, body: &str) -> String {
+    let head = format!(
+        "POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        path,
+        body.len()
+    );
+    send(address, &[head.as_bytes(), body.as_bytes()].concat())
+}
+
+fn still_answers(address: SocketAddr) {
+    let response = post(address, "/day1", "1721\n979\n366\n299\n675\n1456\n");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.contains(r#""part1":514579,"part2":241861950,"error":null"#));
+}
+
+#[test]
+fn solves_in_a_child() {
+    let address = start(Config::default());
+    still_answers(address);
+
+    let response = post(address, "/day1", "1721\nabc\n");
+    assert!(response.starts_with("HTTP/1.1 400 "), "{}", response);
+    assert!(response.contains("parse failed: day1 line 2, column 1"));
+
+    let response = post(address, "/day12", "R900000000\nF10\n");
+    assert!(response.starts_with("HTTP/1.1 200 "), "{}", response);
+    still_answers(address);
+}
+
+#[test]
+fn survives_a_stack_overflow() {
+    // A chain of bags each holding the next, too deep for the recursion that counts them.
+    let mut rules = String::from("shiny gold bags contain 1 bag0 x bag.\n");
+    for i in 0..50_000 {
+        rules += &format!("bag{} x bags contain 1 bag{} x bag.\n", i, i + 1);
+    }
+    rules += "bag50000 x bags contain no other bags.\n";
+
+    let address = start(Config {
+        max_body: 4 * 1024 * 1024,
+        ..Config::default()
+    });
+    let response = post(address, "/day7", &rules);
+    assert!(response.starts_with("HTTP/1.1 500 "), "{}", response);
+    assert!(response.contains("crashed: "), "{}", response);
+    still_answers(address);
+}
+
+#[test]
+fn kills_a_solve_past_its_deadline() {
+    let address = start(Config {
+        timeout: Duration::from_millis(50),
+        max_in_flight: 1,
+        ..Config::default()
+    });
+    let started = Instant::now();
+    let response = post(address, "/day15", "0,3,6\n");
+    assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+    assert!(response.contains("stopped after"), "{}", response);
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // The child was killed, so its place is free again once the connection has closed.
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        let response = send(address, b"GET / HTTP/1.1\r\n\r\n");
+        if response.starts_with("HTTP/1.1 200 ") {
+            break;
+        }
+        assert!(Instant::now() < deadline, "{}", response);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
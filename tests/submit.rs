@@ -0,0 +1,178 @@
+use adventofcode2020::answer::Answer;
+use adventofcode2020::http::{Request, Response};
+use adventofcode2020::submit::{self, Config, History, Refusal, Verdict};
+use adventofcode2020::testing::{stand_in, Scratch};
+use std::fs;
+use std::net::TcpListener;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+// A stand-in for the puzzle site, answering each attempt with the next of `pages`.
+fn mock_site(pages: &[&str]) -> (String, Receiver<Request>) {
+    let responses = pages
+        .iter()
+        .map(|page| Response::new(200, "text/html", format!("<main>{}</main>", page)))
+        .collect();
+    let (url, requests) = stand_in(responses);
+    (url.to_string(), requests)
+}
+
+// A scratch directory holding a session token.
+fn with_token(name: &str) -> Scratch {
+    let scratch = Scratch::new(&format!("submit-{}", name));
+    fs::write(scratch.join("session"), "abc123\n").unwrap();
+    scratch
+}
+
+fn config_for(scratch: &Scratch, url: &str) -> Config {
+    Config {
+        base_url: url.to_string(),
+        session_file: scratch.join("session"),
+        insecure: false,
+        history_file: scratch.join("submissions.txt"),
+        cooldown: Duration::ZERO,
+        timeout: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn correct_answer() {
+    let scratch = with_token("correct");
+    let (url, requests) = mock_site(&["That's the right answer!  You are one gold star closer."]);
+    let config = config_for(&scratch, &url);
+    let answer = Answer::from(514579usize);
+
+    assert_eq!(
+        submit::submit(1, 1, &answer, &config).unwrap(),
+        Verdict::Correct
+    );
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/base/day/1/answer");
+    assert_eq!(request.header("Cookie"), Some("session=abc123"));
+    assert_eq!(
+        request.header("Content-Type"),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(request.body, b"level=1&answer=514579");
+
+    let history = History::load(&config.history_file).unwrap();
+    assert_eq!(history.0.len(), 1);
+    assert_eq!(history.0[0].answer, answer);
+    assert_eq!(history.0[0].verdict, Verdict::Correct);
+
+    // There is nothing more to learn about a solved part.
+    match submit::submit(1, 1, &answer, &config) {
+        Err(submit::Error::Refused(Refusal::Solved(solved))) => assert_eq!(solved, answer),
+        other => panic!("expected a refusal, got {:?}", other),
+    }
+}
+
+#[test]
+fn wrong_answers_are_not_resent() {
+    let scratch = with_token("wrong");
+    let (url, requests) = mock_site(&[
+        "That's not the right answer; your answer is too high.",
+        "That's not the right answer; your answer is too low.",
+        "That's not the right answer.",
+    ]);
+    let config = config_for(&scratch, &url);
+    let submit = |n: usize| submit::submit(7, 2, &Answer::from(n), &config);
+
+    assert_eq!(submit(900).unwrap(), Verdict::TooHigh);
+    assert_eq!(submit(100).unwrap(), Verdict::TooLow);
+    assert_eq!(submit(500).unwrap(), Verdict::Wrong);
+    for n in &[900, 950, 100, 7, 500] {
+        let error = submit(*n).unwrap_err();
+        assert!(
+            matches!(error, submit::Error::Refused(Refusal::KnownWrong(..))),
+            "{}",
+            error
+        );
+    }
+    assert_eq!(requests.iter().count(), 3);
+
+    // The history outlives the process.
+    let history = fs::read_to_string(&config.history_file).unwrap();
+    let verdicts: Vec<&str> = history
+        .lines()
+        .skip(1)
+        .map(|line| line.split(' ').nth(3).unwrap())
+        .collect();
+    assert_eq!(verdicts, ["too-high", "too-low", "wrong"]);
+}
+
+#[test]
+fn cooldown() {
+    let scratch = with_token("cooldown");
+    let (url, requests) = mock_site(&[
+        "You gave an answer too recently; you have to wait after submitting an answer before \
+         trying again.  You have 4m 32s left to wait.",
+    ]);
+    let config = config_for(&scratch, &url);
+
+    assert_eq!(
+        submit::submit(3, 1, &Answer::from(12usize), &config).unwrap(),
+        Verdict::Wait(Some(Duration::from_secs(272)))
+    );
+    // The site asked for longer than the configured cooldown, so the next attempt waits for it.
+    match submit::submit(3, 1, &Answer::from(12usize), &config) {
+        Err(submit::Error::Refused(Refusal::Cooldown(left))) => {
+            assert!(left > Duration::from_secs(260) && left <= Duration::from_secs(272))
+        }
+        other => panic!("expected a refusal, got {:?}", other),
+    }
+
+    let config = Config {
+        cooldown: Duration::from_secs(3600),
+        ..config_for(&scratch, &url)
+    };
+    let error = submit::submit(4, 1, &Answer::from(1usize), &config).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("not submitted: the last attempt was too recent; wait 59m"),
+        "{}",
+        error
+    );
+    assert_eq!(requests.iter().count(), 1);
+}
+
+#[test]
+fn unrecognized_pages_are_recorded() {
+    let scratch = with_token("unrecognized");
+    let (url, requests) = mock_site(&["Please log in."]);
+    let config = Config {
+        cooldown: Duration::from_secs(3600),
+        ..config_for(&scratch, &url)
+    };
+    let error = submit::submit(5, 1, &Answer::from(3usize), &config).unwrap_err();
+    assert!(
+        error.to_string().ends_with("answered with no verdict"),
+        "{}",
+        error
+    );
+    let history = History::load(&config.history_file).unwrap();
+    assert_eq!(history.0.len(), 1);
+    assert_eq!(history.0[0].verdict, Verdict::Unknown);
+
+    // The answer was sent, so the cooldown applies, but it is not known to be wrong.
+    match submit::submit(5, 1, &Answer::from(3usize), &config) {
+        Err(submit::Error::Refused(Refusal::Cooldown(_))) => {}
+        other => panic!("expected a refusal, got {:?}", other),
+    }
+    assert_eq!(requests.iter().count(), 1);
+}
+
+#[test]
+fn unsent_answers_are_not_recorded() {
+    // Nothing listens on a port just given up.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/base", listener.local_addr().unwrap());
+    drop(listener);
+
+    let scratch = with_token("unsent");
+    let config = config_for(&scratch, &url);
+    assert!(submit::submit(5, 1, &Answer::from(3usize), &config).is_err());
+    assert!(!config.history_file.exists());
+}